@@ -1,21 +1,40 @@
 pub use paste::paste;
 
+#[cfg(feature = "serde")]
+#[doc(hidden)]
+pub use serde as __serde;
+
 pub trait NonZeroEquivalent {
     type NonZeroEquivalent;
 }
 
+#[diagnostic::on_unimplemented(
+    message = "`{Self}` is not a `NonZero*` integer",
+    note = "`NonZeroNewtypeImpl!` requires a `NonZero*` inner type, use `UintNewtypeImpl!` for plain integers"
+)]
 pub trait Primitive {
     type Primative;
 }
 
+/// the smallest & largest value of a primitive integer
+pub trait IntBounds {
+    const MIN: Self;
+    const MAX: Self;
+}
+
 macro_rules! impl_relationship {
-    ($unsigned:ty, $non_zero:path) => {
-        impl NonZeroEquivalent for $unsigned {
+    ($primitive:ty, $non_zero:path) => {
+        impl NonZeroEquivalent for $primitive {
             type NonZeroEquivalent = $non_zero;
         }
 
         impl Primitive for $non_zero {
-            type Primative = $unsigned;
+            type Primative = $primitive;
+        }
+
+        impl IntBounds for $primitive {
+            const MIN: Self = <$primitive>::MIN;
+            const MAX: Self = <$primitive>::MAX;
         }
     };
 }
@@ -26,487 +45,5870 @@ impl_relationship!(u32, std::num::NonZeroU32);
 impl_relationship!(u64, std::num::NonZeroU64);
 impl_relationship!(u128, std::num::NonZeroU128);
 impl_relationship!(usize, std::num::NonZeroUsize);
+impl_relationship!(i8, std::num::NonZeroI8);
+impl_relationship!(i16, std::num::NonZeroI16);
+impl_relationship!(i32, std::num::NonZeroI32);
+impl_relationship!(i64, std::num::NonZeroI64);
+impl_relationship!(i128, std::num::NonZeroI128);
+impl_relationship!(isize, std::num::NonZeroIsize);
+
+/// big-endian bytes of the same width on every platform, `usize` & `isize` are always 8 bytes
+pub trait FixedWidthBytes: Sized {
+    type Bytes;
+
+    fn to_fixed_width_bytes(self) -> Self::Bytes;
+
+    /// `None` if the value doesn't fit this platform's `usize`/`isize`
+    fn from_fixed_width_bytes(bytes: Self::Bytes) -> Option<Self>;
+}
+
+macro_rules! impl_fixed_width_bytes {
+    ($int:ty, $wide:ty) => {
+        impl FixedWidthBytes for $int {
+            type Bytes = [u8; std::mem::size_of::<$wide>()];
+
+            fn to_fixed_width_bytes(self) -> Self::Bytes {
+                (self as $wide).to_be_bytes()
+            }
+
+            #[allow(clippy::useless_conversion)]
+            fn from_fixed_width_bytes(bytes: Self::Bytes) -> Option<Self> {
+                <$int>::try_from(<$wide>::from_be_bytes(bytes)).ok()
+            }
+        }
+    };
+}
+
+impl_fixed_width_bytes!(u8, u8);
+impl_fixed_width_bytes!(u16, u16);
+impl_fixed_width_bytes!(u32, u32);
+impl_fixed_width_bytes!(u64, u64);
+impl_fixed_width_bytes!(u128, u128);
+impl_fixed_width_bytes!(usize, u64);
+impl_fixed_width_bytes!(i8, i8);
+impl_fixed_width_bytes!(i16, i16);
+impl_fixed_width_bytes!(i32, i32);
+impl_fixed_width_bytes!(i64, i64);
+impl_fixed_width_bytes!(i128, i128);
+impl_fixed_width_bytes!(isize, i64);
+
+/// `FixedWidthBytes` that sort like the values, signed integers have their sign bit flipped
+pub trait OrderedBytes: FixedWidthBytes {
+    fn to_ordered_bytes(self) -> Self::Bytes;
+
+    /// `None` if the value doesn't fit this platform's `usize`/`isize`
+    fn from_ordered_bytes(bytes: Self::Bytes) -> Option<Self>;
+}
+
+macro_rules! impl_ordered_bytes {
+    (unsigned $uint:ty) => {
+        impl OrderedBytes for $uint {
+            fn to_ordered_bytes(self) -> Self::Bytes {
+                self.to_fixed_width_bytes()
+            }
+
+            fn from_ordered_bytes(bytes: Self::Bytes) -> Option<Self> {
+                Self::from_fixed_width_bytes(bytes)
+            }
+        }
+    };
+    (signed $int:ty) => {
+        impl OrderedBytes for $int {
+            fn to_ordered_bytes(self) -> Self::Bytes {
+                let mut bytes = self.to_fixed_width_bytes();
+                bytes[0] ^= 0x80;
+                bytes
+            }
+
+            fn from_ordered_bytes(mut bytes: Self::Bytes) -> Option<Self> {
+                bytes[0] ^= 0x80;
+                Self::from_fixed_width_bytes(bytes)
+            }
+        }
+    };
+}
+
+impl_ordered_bytes!(unsigned u8);
+impl_ordered_bytes!(unsigned u16);
+impl_ordered_bytes!(unsigned u32);
+impl_ordered_bytes!(unsigned u64);
+impl_ordered_bytes!(unsigned u128);
+impl_ordered_bytes!(unsigned usize);
+impl_ordered_bytes!(signed i8);
+impl_ordered_bytes!(signed i16);
+impl_ordered_bytes!(signed i32);
+impl_ordered_bytes!(signed i64);
+impl_ordered_bytes!(signed i128);
+impl_ordered_bytes!(signed isize);
+
+/// the stored encoding, implemented by the newtype `*Impl!` macros, implement it by hand for
+/// other types to get `item::Store` / `map::Store` through their `Key` / `Prefix` traits
+#[diagnostic::on_unimplemented(
+    message = "`{Self}` has no stored encoding",
+    note = "derive one of the newtype `*Impl!` macros alongside `ItemStoreImpl!`/`MapStoreImpl!`"
+)]
+pub trait ByteSerde: Sized {
+    fn from_owned_bytes(bytes: Vec<u8>) -> Self;
+
+    /// infallible unless overridden, the generated impls report every decoding error
+    fn try_from_owned_bytes(bytes: Vec<u8>) -> Result<Self, LoadError> {
+        Ok(Self::from_owned_bytes(bytes))
+    }
+
+    fn to_owned_bytes(&self) -> Vec<u8>;
+
+    /// the stored length when every value encodes to the same number of bytes, version byte
+    /// included
+    const FIXED_LEN: Option<usize> = None;
+}
+
+/// utf-8 bytes, for `String` elements of collection newtypes
+impl ByteSerde for String {
+    fn from_owned_bytes(bytes: Vec<u8>) -> Self {
+        Self::try_from_owned_bytes(bytes).expect("stored valid value")
+    }
+
+    fn try_from_owned_bytes(bytes: Vec<u8>) -> Result<Self, LoadError> {
+        String::from_utf8(bytes).map_err(|_| LoadError::InvalidUtf8)
+    }
+
+    fn to_owned_bytes(&self) -> Vec<u8> {
+        self.as_bytes().to_owned()
+    }
+}
+
+/// `ByteSerde`, `TryFrom<Vec<u8>>` & `TryFrom<&[u8]>` forwarding to the inherent codec generated
+/// by a newtype `*Impl!` macro
+#[macro_export]
+macro_rules! impl_byte_serde {
+    ($Newtype:ident $(, fixed_len = $fixed_len:expr)?) => {
+        impl $crate::ByteSerde for $Newtype {
+            $(const FIXED_LEN: Option<usize> = Some($fixed_len);)?
+
+            fn from_owned_bytes(bytes: Vec<u8>) -> Self {
+                Self::from_owned_bytes(bytes)
+            }
+
+            fn try_from_owned_bytes(bytes: Vec<u8>) -> Result<Self, $crate::LoadError> {
+                Self::try_from_owned_bytes(bytes)
+            }
+
+            fn to_owned_bytes(&self) -> Vec<u8> {
+                Self::to_owned_bytes(self)
+            }
+        }
+
+        impl TryFrom<Vec<u8>> for $Newtype {
+            type Error = $crate::LoadError;
+
+            fn try_from(bytes: Vec<u8>) -> Result<Self, Self::Error> {
+                Self::try_from_owned_bytes(bytes)
+            }
+        }
+
+        impl TryFrom<&[u8]> for $Newtype {
+            type Error = $crate::LoadError;
+
+            fn try_from(bytes: &[u8]) -> Result<Self, Self::Error> {
+                Self::try_from_owned_bytes(bytes.to_vec())
+            }
+        }
+    };
+}
+
+/// serde impls (de)serializing the inner value directly, so non-self-describing formats such as
+/// bincode see exactly the inner value, `@string` goes through `TryNew` on the way in and the
+/// integer newtypes through their `check_valid`
+#[cfg(feature = "serde")]
+#[macro_export]
+macro_rules! impl_serde {
+    ($Newtype:ident, $Inner:ty) => {
+        impl $crate::__serde::Serialize for $Newtype {
+            fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+            where
+                S: $crate::__serde::Serializer,
+            {
+                $crate::__serde::Serialize::serialize(&self.0, serializer)
+            }
+        }
+
+        impl<'de> $crate::__serde::Deserialize<'de> for $Newtype {
+            fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+            where
+                D: $crate::__serde::Deserializer<'de>,
+            {
+                let value =
+                    <$Inner as $crate::__serde::Deserialize<'de>>::deserialize(deserializer)
+                        .map(Self)?;
+
+                value
+                    .check_valid()
+                    .map_err(<D::Error as $crate::__serde::de::Error>::custom)?;
+
+                Ok(value)
+            }
+        }
+    };
+    (@string $Newtype:ident) => {
+        impl $crate::__serde::Serialize for $Newtype {
+            fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+            where
+                S: $crate::__serde::Serializer,
+            {
+                serializer.serialize_str(<Self as $crate::string::Newtype>::as_str(self))
+            }
+        }
+
+        impl<'de> $crate::__serde::Deserialize<'de> for $Newtype {
+            fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+            where
+                D: $crate::__serde::Deserializer<'de>,
+            {
+                let s = <String as $crate::__serde::Deserialize<'de>>::deserialize(deserializer)?;
+
+                <Self as $crate::string::TryNew>::try_new(s)
+                    .map_err(<D::Error as $crate::__serde::de::Error>::custom)
+            }
+        }
+    };
+}
+
+/// without the `serde` feature there are no serde impls
+#[cfg(not(feature = "serde"))]
+#[macro_export]
+macro_rules! impl_serde {
+    ($_Newtype:ident, $_Inner:ty) => {};
+    (@string $_Newtype:ident) => {};
+}
+
+/// fails to compile unless `$Newtype` has the size of its single `$Inner` field, the newtype
+/// `*Impl!` macros don't re-emit the struct so they can't add `#[repr(transparent)]` themselves
+#[macro_export]
+macro_rules! assert_transparent_size {
+    ($Newtype:ident, $Inner:ty) => {
+        const _: () = assert!(
+            std::mem::size_of::<$Newtype>() == std::mem::size_of::<$Inner>(),
+            concat!(
+                "`",
+                stringify!($Newtype),
+                "` must have the size of its inner field"
+            )
+        );
+    };
+}
+
+/// invariants of a newtype, checked on `try_new`, load & deserialize for
+/// `custom(<kind>_newtype(validate))`, the infallible constructors panic on a rejected value
+pub trait Validate {
+    fn validate(&self) -> Result<(), ValidationError>;
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ValidationError(pub String);
+
+impl std::fmt::Display for ValidationError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "invalid value: {}", self.0)
+    }
+}
+
+impl std::error::Error for ValidationError {}
+
+/// why stored bytes couldn't be decoded into a newtype
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum LoadError {
+    ByteLength {
+        expected: usize,
+        found: usize,
+    },
+    Zero,
+    InvalidUtf8,
+    StringLen(string::StringLenError),
+    InvalidDiscriminant(u8),
+    VersionMismatch {
+        expected: u8,
+        found: u8,
+    },
+    Address(address::AddressError),
+    /// the stored `usize`/`isize` doesn't fit this platform's width
+    Overflow,
+    /// the stored bytes of a `custom(string_newtype(compress))` newtype are corrupt
+    Decompress,
+    /// rejected by the `Validate` impl of a `custom(<kind>_newtype(validate))` newtype
+    Validation(ValidationError),
+}
+
+impl std::fmt::Display for LoadError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            LoadError::ByteLength { expected, found } => {
+                write!(f, "expected {expected} stored bytes, found {found}")
+            }
+            LoadError::Zero => f.write_str("stored zero for a non-zero newtype"),
+            LoadError::InvalidUtf8 => f.write_str("stored invalid utf-8"),
+            LoadError::StringLen(err) => err.fmt(f),
+            LoadError::InvalidDiscriminant(d) => write!(f, "stored invalid discriminant {d}"),
+            LoadError::VersionMismatch { expected, found } => {
+                write!(f, "expected stored version {expected}, found {found}")
+            }
+            LoadError::Address(err) => err.fmt(f),
+            LoadError::Overflow => f.write_str("stored integer overflows this platform's width"),
+            LoadError::Decompress => f.write_str("stored corrupt compressed bytes"),
+            LoadError::Validation(err) => err.fmt(f),
+        }
+    }
+}
+
+impl std::error::Error for LoadError {}
+
+impl From<string::StringLenError> for LoadError {
+    fn from(err: string::StringLenError) -> Self {
+        LoadError::StringLen(err)
+    }
+}
+
+impl From<ValidationError> for LoadError {
+    fn from(err: ValidationError) -> Self {
+        LoadError::Validation(err)
+    }
+}
+
+impl From<address::AddressError> for LoadError {
+    fn from(err: address::AddressError) -> Self {
+        LoadError::Address(err)
+    }
+}
 
 pub trait ReadonlyStorage {
     fn get(&self, key: &[u8]) -> Option<Vec<u8>>;
+
+    fn get_key(&self, key: &map::StorageKey) -> Option<Vec<u8>> {
+        self.get(key.as_bytes())
+    }
+
+    /// one `get` per key unless overridden, for backends that batch reads more cheaply
+    fn get_many(&self, keys: &[&[u8]]) -> Vec<Option<Vec<u8>>> {
+        keys.iter().map(|key| self.get(key)).collect()
+    }
 }
 
 pub trait MutableStorage {
     fn set(&mut self, key: &[u8], value: &[u8]);
 
     fn clear(&mut self, key: &[u8]);
+
+    fn set_key(&mut self, key: &map::StorageKey, value: &[u8]) {
+        self.set(key.as_bytes(), value)
+    }
+
+    fn clear_key(&mut self, key: &map::StorageKey) {
+        self.clear(key.as_bytes())
+    }
 }
 
-pub mod item {
-    use crate::{MutableStorage, ReadonlyStorage};
+/// why a fallible backend couldn't complete a write
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct StorageError(pub String);
 
-    pub trait Store: Sized {
-        fn load(storage: &dyn ReadonlyStorage) -> Option<Self>;
+impl std::fmt::Display for StorageError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "storage write failed: {}", self.0)
+    }
+}
 
-        fn save(&self, storage: &mut dyn MutableStorage);
+impl std::error::Error for StorageError {}
+
+/// `MutableStorage` for backends whose writes can fail, every `MutableStorage` is one that never
+/// does
+pub trait TryMutableStorage: ReadonlyStorage {
+    fn try_set(&mut self, key: &[u8], value: &[u8]) -> Result<(), StorageError>;
+
+    fn try_clear(&mut self, key: &[u8]) -> Result<(), StorageError>;
+}
+
+impl<S> TryMutableStorage for S
+where
+    S: ReadonlyStorage + MutableStorage + ?Sized,
+{
+    fn try_set(&mut self, key: &[u8], value: &[u8]) -> Result<(), StorageError> {
+        self.set(key, value);
+        Ok(())
     }
 
-    pub trait Clear {
-        fn clear(storage: &mut dyn MutableStorage);
+    fn try_clear(&mut self, key: &[u8]) -> Result<(), StorageError> {
+        self.clear(key);
+        Ok(())
     }
+}
 
-    pub trait LoadAlways: Sized {
-        fn load_always(storage: &dyn ReadonlyStorage) -> Self;
+pub trait IterableStorage: ReadonlyStorage {
+    /// all entries whose key starts with `prefix`, in ascending key order
+    fn prefix_iter(&self, prefix: &[u8]) -> Box<dyn Iterator<Item = (Vec<u8>, Vec<u8>)> + '_>;
+
+    /// `prefix_iter` in descending key order, collects every entry unless overridden by a backend
+    /// that can seek to the end of the prefix
+    fn prefix_iter_rev(&self, prefix: &[u8]) -> Box<dyn Iterator<Item = (Vec<u8>, Vec<u8>)> + '_> {
+        Box::new(
+            self.prefix_iter(prefix)
+                .collect::<Vec<_>>()
+                .into_iter()
+                .rev(),
+        )
     }
+}
 
-    /// marker trait, making `Clear` & `LoadAlways` mutually exclusive
-    pub trait ClearOrLoadAlways {}
+/// a single handle to read & write through, for the read-modify-write helpers
+pub trait ReadWriteStorage: ReadonlyStorage + MutableStorage {}
 
-    #[macro_export]
-    macro_rules! item_store_derive_attrs {
-        ($Item:ident, custom(item_store(always))) => {
-            impl $crate::item::ClearOrLoadAlways for $Item {}
+impl<T> ReadWriteStorage for T where T: ReadonlyStorage + MutableStorage + ?Sized {}
 
-            impl $crate::item::LoadAlways for $Item {
-                fn load_always(storage: &dyn $crate::ReadonlyStorage) -> Self {
-                    Self::load(storage).expect("always present in storage")
-                }
-            }
-        };
-        ($Item:ident, custom(item_store(clear))) => {
-            impl $crate::item::ClearOrLoadAlways for $Item {}
+pub trait IterableMutableStorage: IterableStorage + MutableStorage {}
 
-            impl $crate::item::Clear for $Item {
-                fn clear(storage: &mut dyn $crate::MutableStorage) {
-                    storage.clear(Self::KEY.as_bytes());
-                }
-            }
-        };
-        ($_Item:ident, $($_other_meta:tt)+) => {};
+impl<T> IterableMutableStorage for T where T: IterableStorage + MutableStorage + ?Sized {}
+
+impl<S> ReadonlyStorage for &S
+where
+    S: ReadonlyStorage + ?Sized,
+{
+    fn get(&self, key: &[u8]) -> Option<Vec<u8>> {
+        (**self).get(key)
     }
 
-    #[macro_export]
-    macro_rules! ItemStoreImpl {
-        (
-        $(#[$($meta_item:tt)+])*
-        $pub:vis struct $Item:ident($Inner:ident);
-    ) => {
-            impl $Item {
-                $crate::paste! {
-                    const KEY: &'static str = concat!(module_path!(), "::", stringify!([< $Item:snake _ $Inner:snake >]));
-                }
-            }
+    fn get_many(&self, keys: &[&[u8]]) -> Vec<Option<Vec<u8>>> {
+        (**self).get_many(keys)
+    }
+}
 
-            impl $crate::item::Store for $Item {
-                fn load(storage: &dyn $crate::ReadonlyStorage) -> Option<Self> {
-                    storage.get(Self::KEY.as_bytes()).map(Self::from_owned_bytes)
-                }
+impl<S> ReadonlyStorage for &mut S
+where
+    S: ReadonlyStorage + ?Sized,
+{
+    fn get(&self, key: &[u8]) -> Option<Vec<u8>> {
+        (**self).get(key)
+    }
 
-                fn save(&self, storage: &mut dyn $crate::MutableStorage) {
-                    storage.set(Self::KEY.as_bytes(), self.to_owned_bytes().as_slice());
-                }
-            }
+    fn get_many(&self, keys: &[&[u8]]) -> Vec<Option<Vec<u8>>> {
+        (**self).get_many(keys)
+    }
+}
 
-            $(
-                $crate::item_store_derive_attrs!($Item, $($meta_item)+);
-            )*
-        };
+impl<S> MutableStorage for &mut S
+where
+    S: MutableStorage + ?Sized,
+{
+    fn set(&mut self, key: &[u8], value: &[u8]) {
+        (**self).set(key, value)
+    }
+
+    fn clear(&mut self, key: &[u8]) {
+        (**self).clear(key)
     }
 }
 
-pub mod map {
-    use crate::{MutableStorage, ReadonlyStorage};
+impl<S> IterableStorage for &S
+where
+    S: IterableStorage + ?Sized,
+{
+    fn prefix_iter(&self, prefix: &[u8]) -> Box<dyn Iterator<Item = (Vec<u8>, Vec<u8>)> + '_> {
+        (**self).prefix_iter(prefix)
+    }
 
-    pub trait IntoMapKey {
-        fn into_map_key(self) -> String;
+    fn prefix_iter_rev(&self, prefix: &[u8]) -> Box<dyn Iterator<Item = (Vec<u8>, Vec<u8>)> + '_> {
+        (**self).prefix_iter_rev(prefix)
+    }
+}
+
+impl<S> IterableStorage for &mut S
+where
+    S: IterableStorage + ?Sized,
+{
+    fn prefix_iter(&self, prefix: &[u8]) -> Box<dyn Iterator<Item = (Vec<u8>, Vec<u8>)> + '_> {
+        (**self).prefix_iter(prefix)
     }
 
-    impl<T1, T2> IntoMapKey for (T1, T2)
+    fn prefix_iter_rev(&self, prefix: &[u8]) -> Box<dyn Iterator<Item = (Vec<u8>, Vec<u8>)> + '_> {
+        (**self).prefix_iter_rev(prefix)
+    }
+}
+
+/// typed access through a storage handle, e.g. `storage.get_item::<FooUint>()`
+pub trait StorageExt: ReadonlyStorage {
+    fn get_item<T>(&self) -> Option<T>
     where
-        T1: IntoMapKey,
-        T2: IntoMapKey,
+        Self: Sized,
+        T: item::Store,
     {
-        fn into_map_key(self) -> String {
-            let mut key = self.0.into_map_key();
-            key.push(':');
-            key.push_str(self.1.into_map_key().as_str());
-            key
-        }
+        T::load(self)
     }
 
-    macro_rules! impl_to_map_key_uint {
-        ($uint:ty) => {
-            impl IntoMapKey for $uint {
-                fn into_map_key(self) -> String {
-                    self.to_string()
-                }
-            }
-        };
+    fn set_item<T>(&mut self, item: &T)
+    where
+        Self: Sized + MutableStorage,
+        T: item::Store,
+    {
+        item.save(self)
     }
 
-    macro_rules! impl_to_map_key_non_zero {
-        ($nz:ty) => {
-            impl IntoMapKey for $nz {
-                fn into_map_key(self) -> String {
-                    self.get().to_string()
-                }
-            }
-        };
+    fn get_map<T>(&self, key: impl std::borrow::Borrow<T::MapKeyType>) -> Option<T>
+    where
+        Self: Sized,
+        T: map::Store,
+    {
+        T::load_at(self, key)
     }
 
-    impl_to_map_key_uint!(u8);
-    impl_to_map_key_uint!(u16);
-    impl_to_map_key_uint!(u32);
-    impl_to_map_key_uint!(u64);
-    impl_to_map_key_uint!(u128);
-    impl_to_map_key_uint!(usize);
-    impl_to_map_key_non_zero!(std::num::NonZeroU8);
-    impl_to_map_key_non_zero!(std::num::NonZeroU16);
-    impl_to_map_key_non_zero!(std::num::NonZeroU32);
-    impl_to_map_key_non_zero!(std::num::NonZeroU64);
-    impl_to_map_key_non_zero!(std::num::NonZeroU128);
-    impl_to_map_key_non_zero!(std::num::NonZeroUsize);
-
-    impl IntoMapKey for String {
-        fn into_map_key(self) -> String {
-            self
-        }
+    fn set_map<T>(&mut self, key: impl std::borrow::Borrow<T::MapKeyType>, item: &T)
+    where
+        Self: Sized + MutableStorage,
+        T: map::Store,
+    {
+        item.save_at(self, key)
     }
+}
 
-    pub trait MapKeyType {
-        type MapKeyType;
+impl<S> StorageExt for S where S: ReadonlyStorage {}
+
+/// buffers writes over `storage`, nothing reaches it until `commit`
+pub struct BufferedStorage<'a> {
+    storage: &'a mut dyn ReadWriteStorage,
+    // `None` marks a buffered clear
+    overlay: std::collections::BTreeMap<Vec<u8>, Option<Vec<u8>>>,
+}
+
+impl<'a> BufferedStorage<'a> {
+    pub fn new(storage: &'a mut dyn ReadWriteStorage) -> Self {
+        Self {
+            storage,
+            overlay: std::collections::BTreeMap::new(),
+        }
     }
 
-    #[macro_export]
-    macro_rules! MapKeyImpl {
-        (
-        $(#[$($meta_item:tt)+])*
-        $pub:vis struct $Item:ident($Inner:ident);
-    ) => {
-            impl $crate::map::IntoMapKey for $Item {
-                fn into_map_key(self) -> String {
-                    self.0.into_map_key()
-                }
+    /// writes every buffered `set` & `clear` to the underlying storage
+    pub fn commit(self) {
+        for (key, value) in self.overlay {
+            match value {
+                Some(value) => self.storage.set(&key, &value),
+                None => self.storage.clear(&key),
             }
-        };
+        }
     }
 
-    pub trait Store: Sized + MapKeyType {
-        fn load_at(storage: &dyn ReadonlyStorage, key: Self::MapKeyType) -> Option<Self>;
+    /// drops every buffered write
+    pub fn rollback(self) {}
+}
 
-        fn save_at(&self, storage: &mut dyn MutableStorage, key: Self::MapKeyType);
+impl ReadonlyStorage for BufferedStorage<'_> {
+    fn get(&self, key: &[u8]) -> Option<Vec<u8>> {
+        match self.overlay.get(key) {
+            Some(value) => value.clone(),
+            None => self.storage.get(key),
+        }
     }
+}
 
-    pub trait ClearAt: MapKeyType {
-        fn clear_at(storage: &mut dyn MutableStorage, key: Self::MapKeyType);
+impl MutableStorage for BufferedStorage<'_> {
+    fn set(&mut self, key: &[u8], value: &[u8]) {
+        self.overlay.insert(key.to_owned(), Some(value.to_owned()));
     }
 
-    pub trait LoadAlwaysAt: Sized + MapKeyType {
-        fn load_always_at(storage: &dyn ReadonlyStorage, key: Self::MapKeyType) -> Self;
+    fn clear(&mut self, key: &[u8]) {
+        self.overlay.insert(key.to_owned(), None);
     }
+}
+
+/// prepends `namespace` to every key read from `storage`
+pub struct ReadonlyNamespacedStorage<'a> {
+    storage: &'a dyn ReadonlyStorage,
+    namespace: Vec<u8>,
+}
+
+impl<'a> ReadonlyNamespacedStorage<'a> {
+    pub fn new(storage: &'a dyn ReadonlyStorage, namespace: impl Into<Vec<u8>>) -> Self {
+        Self {
+            storage,
+            namespace: namespace.into(),
+        }
+    }
+}
+
+impl ReadonlyStorage for ReadonlyNamespacedStorage<'_> {
+    fn get(&self, key: &[u8]) -> Option<Vec<u8>> {
+        self.storage.get(&namespaced(&self.namespace, key))
+    }
+}
+
+/// prepends `namespace` to every key read from or written to `storage`
+pub struct NamespacedStorage<'a> {
+    storage: &'a mut dyn ReadWriteStorage,
+    namespace: Vec<u8>,
+}
+
+impl<'a> NamespacedStorage<'a> {
+    pub fn new(storage: &'a mut dyn ReadWriteStorage, namespace: impl Into<Vec<u8>>) -> Self {
+        Self {
+            storage,
+            namespace: namespace.into(),
+        }
+    }
+}
+
+impl ReadonlyStorage for NamespacedStorage<'_> {
+    fn get(&self, key: &[u8]) -> Option<Vec<u8>> {
+        self.storage.get(&namespaced(&self.namespace, key))
+    }
+}
+
+impl MutableStorage for NamespacedStorage<'_> {
+    fn set(&mut self, key: &[u8], value: &[u8]) {
+        self.storage.set(&namespaced(&self.namespace, key), value)
+    }
+
+    fn clear(&mut self, key: &[u8]) {
+        self.storage.clear(&namespaced(&self.namespace, key))
+    }
+}
+
+fn namespaced(namespace: &[u8], key: &[u8]) -> Vec<u8> {
+    [namespace, key].concat()
+}
+
+pub mod version {
+    use crate::LoadError;
+
+    /// prepends `version`, if any, to the encoded bytes
+    pub fn tag(version: Option<u8>, mut bytes: Vec<u8>) -> Vec<u8> {
+        if let Some(version) = version {
+            bytes.insert(0, version);
+        }
+
+        bytes
+    }
+
+    /// strips and checks the leading version byte, if `version` is set
+    pub fn untag(version: Option<u8>, mut bytes: Vec<u8>) -> Result<Vec<u8>, LoadError> {
+        let Some(expected) = version else {
+            return Ok(bytes);
+        };
+
+        let Some(&found) = bytes.first() else {
+            return Err(LoadError::ByteLength {
+                expected: 1,
+                found: 0,
+            });
+        };
+
+        if found != expected {
+            return Err(LoadError::VersionMismatch { expected, found });
+        }
+
+        bytes.remove(0);
+
+        Ok(bytes)
+    }
+
+    /// `untag` over a borrowed slice
+    pub fn untag_slice(version: Option<u8>, bytes: &[u8]) -> Result<&[u8], LoadError> {
+        let Some(expected) = version else {
+            return Ok(bytes);
+        };
+
+        match bytes.split_first() {
+            Some((&found, rest)) if found == expected => Ok(rest),
+            Some((&found, _)) => Err(LoadError::VersionMismatch { expected, found }),
+            None => Err(LoadError::ByteLength {
+                expected: 1,
+                found: 0,
+            }),
+        }
+    }
+
+    /// `Some(version)` from `custom(<kind>_newtype(versioned))` (version 1) or
+    /// `custom(<kind>_newtype(versioned = N))`, otherwise `None`
+    #[macro_export]
+    macro_rules! newtype_version {
+        () => {
+            None
+        };
+        ([custom($_kind:ident(versioned))] $($_rest:tt)*) => {
+            Some(1)
+        };
+        ([custom($_kind:ident(versioned = $version:literal))] $($_rest:tt)*) => {
+            Some($version)
+        };
+        ([$($_other_meta:tt)+] $($rest:tt)*) => {
+            $crate::newtype_version!($($rest)*)
+        };
+    }
+
+    /// whether `custom(<kind>_newtype(endian = "le"))` is present, big-endian otherwise
+    #[macro_export]
+    macro_rules! newtype_little_endian {
+        () => {
+            false
+        };
+        ([custom($_kind:ident(endian = "le"))] $($_rest:tt)*) => {
+            true
+        };
+        ([$($_other_meta:tt)+] $($rest:tt)*) => {
+            $crate::newtype_little_endian!($($rest)*)
+        };
+    }
+
+    /// `Validate::validate($value)` if `custom(<kind>_newtype(validate))` is present, otherwise
+    /// `Ok(())`
+    #[macro_export]
+    macro_rules! newtype_validate {
+        ($_value:expr;) => {
+            Ok::<(), $crate::ValidationError>(())
+        };
+        ($value:expr; [custom($_kind:ident(validate))] $($_rest:tt)*) => {
+            $crate::Validate::validate($value)
+        };
+        ($value:expr; [$($_other_meta:tt)+] $($rest:tt)*) => {
+            $crate::newtype_validate!($value; $($rest)*)
+        };
+    }
+}
+
+#[cfg(feature = "compress")]
+pub mod compress {
+    use crate::LoadError;
+
+    /// never the first byte of utf-8, so uncompressed strings stored before stay readable
+    const MAGIC: u8 = 0xff;
+    const LEVEL: u8 = 6;
+
+    /// the most bytes `decompress` inflates to, so a small corrupt or hostile payload can't
+    /// allocate without bound, larger values are stored uncompressed
+    pub const MAX_DECOMPRESSED_LEN: usize = 16 * 1024 * 1024;
+
+    /// `MAGIC` followed by the raw deflate stream of `bytes`, `bytes` is returned as is unless
+    /// compressing shrinks it or if it's longer than `MAX_DECOMPRESSED_LEN`
+    pub fn compress(bytes: Vec<u8>) -> Vec<u8> {
+        if bytes.len() > MAX_DECOMPRESSED_LEN {
+            return bytes;
+        }
+
+        let mut compressed = vec![MAGIC];
+        compressed.extend(miniz_oxide::deflate::compress_to_vec(&bytes, LEVEL));
+
+        if compressed.len() < bytes.len() {
+            compressed
+        } else {
+            bytes
+        }
+    }
+
+    /// reverses `compress`, bytes without the `MAGIC` header are returned as is, a stream
+    /// inflating past `MAX_DECOMPRESSED_LEN` is `LoadError::Decompress`
+    pub fn decompress(bytes: Vec<u8>) -> Result<Vec<u8>, LoadError> {
+        let Some((&MAGIC, deflated)) = bytes.split_first() else {
+            return Ok(bytes);
+        };
+
+        miniz_oxide::inflate::decompress_to_vec_with_limit(deflated, MAX_DECOMPRESSED_LEN)
+            .map_err(|_| LoadError::Decompress)
+    }
+}
+
+pub mod frame {
+    use crate::LoadError;
+
+    /// prepends the `u32` big-endian length of `bytes`, so framed values can be concatenated
+    pub fn frame(bytes: Vec<u8>) -> Vec<u8> {
+        let len = u32::try_from(bytes.len()).expect("framed value fits in u32 length");
+
+        let mut framed = len.to_be_bytes().to_vec();
+        framed.extend(bytes);
+        framed
+    }
+
+    /// splits the first framed value off `buf`, returning it and the rest of `buf`
+    pub fn unframe(buf: &[u8]) -> Result<(Vec<u8>, &[u8]), LoadError> {
+        let Some((len, rest)) = buf.split_first_chunk::<4>() else {
+            return Err(LoadError::ByteLength {
+                expected: 4,
+                found: buf.len(),
+            });
+        };
+
+        let len = u32::from_be_bytes(*len) as usize;
+
+        if rest.len() < len {
+            return Err(LoadError::ByteLength {
+                expected: 4 + len,
+                found: buf.len(),
+            });
+        }
+
+        let (value, rest) = rest.split_at(len);
+
+        Ok((value.to_vec(), rest))
+    }
+}
+
+pub mod item {
+    use crate::{
+        ByteSerde, LoadError, MutableStorage, ReadWriteStorage, ReadonlyStorage, StorageError,
+        TryMutableStorage,
+    };
+
+    /// the storage key of an item stored under `prefix`, the same key `ItemStoreImpl!` stores
+    /// under for `custom(item_store(key = prefix))`, `const` so the macro can build `KEY` with it
+    pub const fn build_key(prefix: &str) -> &str {
+        prefix
+    }
+
+    /// the storage key of an item, `item::Store` is implemented for every `Key + ByteSerde`
+    pub trait Key {
+        const KEY: &'static str;
+
+        /// `KEY` as bytes, computed at compile time
+        const KEY_BYTES: &'static [u8] = Self::KEY.as_bytes();
+    }
+
+    /// where a `custom(item_store(keep_previous))` item keeps the value it held before the last save
+    pub fn previous_key(key: &str) -> String {
+        format!("{key}::prev")
+    }
+
+    /// one-step undo, from `custom(item_store(keep_previous))`
+    pub trait KeepPrevious: Key + ByteSerde {
+        /// `save`, first copying the stored bytes to `previous_key(KEY)`, plain `save` can't read
+        /// the value it replaces
+        fn save_keeping_previous(&self, storage: &mut dyn ReadWriteStorage) {
+            let previous_key = previous_key(Self::KEY);
+
+            match storage.get(Self::KEY_BYTES) {
+                Some(current) => storage.set(previous_key.as_bytes(), current.as_slice()),
+                None => storage.clear(previous_key.as_bytes()),
+            }
+
+            storage.set(Self::KEY_BYTES, self.to_owned_bytes().as_slice());
+        }
+
+        /// the value before the last `save_keeping_previous`, `None` if nothing was stored then
+        fn load_previous(storage: &dyn ReadonlyStorage) -> Option<Self> {
+            storage
+                .get(previous_key(Self::KEY).as_bytes())
+                .map(Self::from_owned_bytes)
+        }
+    }
+
+    pub trait Store: Sized {
+        #[must_use]
+        fn load(storage: &dyn ReadonlyStorage) -> Option<Self>;
+
+        fn try_load(storage: &dyn ReadonlyStorage) -> Result<Option<Self>, LoadError>;
+
+        /// whether anything is stored, without decoding it
+        fn exists(storage: &dyn ReadonlyStorage) -> bool;
+
+        fn save(&self, storage: &mut dyn MutableStorage);
+
+        /// `save` through a backend whose writes can fail
+        fn try_save(&self, storage: &mut dyn TryMutableStorage) -> Result<(), StorageError>;
+
+        /// moves the raw bytes stored under `old_key` to the current key, without decoding them
+        fn migrate_key(storage: &mut dyn ReadWriteStorage, old_key: &[u8]);
+    }
+
+    impl<T> Store for T
+    where
+        T: Key + ByteSerde,
+    {
+        fn load(storage: &dyn ReadonlyStorage) -> Option<Self> {
+            storage.get(Self::KEY_BYTES).map(T::from_owned_bytes)
+        }
+
+        fn try_load(storage: &dyn ReadonlyStorage) -> Result<Option<Self>, LoadError> {
+            storage
+                .get(Self::KEY_BYTES)
+                .map(T::try_from_owned_bytes)
+                .transpose()
+        }
+
+        fn exists(storage: &dyn ReadonlyStorage) -> bool {
+            storage.get(Self::KEY_BYTES).is_some()
+        }
+
+        fn save(&self, storage: &mut dyn MutableStorage) {
+            storage.set(Self::KEY_BYTES, self.to_owned_bytes().as_slice());
+        }
+
+        fn try_save(&self, storage: &mut dyn TryMutableStorage) -> Result<(), StorageError> {
+            storage.try_set(Self::KEY_BYTES, self.to_owned_bytes().as_slice())
+        }
+
+        fn migrate_key(storage: &mut dyn ReadWriteStorage, old_key: &[u8]) {
+            if old_key == Self::KEY_BYTES {
+                return;
+            }
+
+            let Some(bytes) = storage.get(old_key) else {
+                return;
+            };
+
+            storage.set(Self::KEY_BYTES, bytes.as_slice());
+            storage.clear(old_key);
+        }
+    }
+
+    /// object safe `Store`, for persisting items of different types behind `dyn DynStore`
+    pub trait DynStore {
+        fn save_dyn(&self, storage: &mut dyn MutableStorage);
+
+        fn key(&self) -> String;
+    }
+
+    impl<T> DynStore for T
+    where
+        T: Key + ByteSerde,
+    {
+        fn save_dyn(&self, storage: &mut dyn MutableStorage) {
+            self.save(storage)
+        }
+
+        fn key(&self) -> String {
+            build_key(Self::KEY).to_owned()
+        }
+    }
+
+    pub trait Clear {
+        fn clear(storage: &mut dyn MutableStorage);
+    }
+
+    pub trait LoadAlways: Sized {
+        fn load_always(storage: &dyn ReadonlyStorage) -> Self;
+    }
+
+    /// `Default` when nothing is stored, not available for non_zero newtypes, combines with `Clear`
+    /// as it doesn't claim `ClearOrLoadAlways`
+    pub trait LoadOrDefault: Sized {
+        fn load_or_default(storage: &dyn ReadonlyStorage) -> Self;
+    }
+
+    /// marker trait, making `Clear` & `LoadAlways` mutually exclusive
+    pub trait ClearOrLoadAlways {}
+
+    #[macro_export]
+    macro_rules! item_store_derive_attrs {
+        ($Item:ident, custom(item_store(always))) => {
+            impl $crate::item::ClearOrLoadAlways for $Item {}
+
+            impl $crate::item::LoadAlways for $Item {
+                fn load_always(storage: &dyn $crate::ReadonlyStorage) -> Self {
+                    <Self as $crate::item::Store>::load(storage).expect("always present in storage")
+                }
+            }
+        };
+        ($Item:ident, custom(item_store(clear))) => {
+            impl $crate::item::ClearOrLoadAlways for $Item {}
+
+            impl $crate::item::Clear for $Item {
+                fn clear(storage: &mut dyn $crate::MutableStorage) {
+                    storage.clear(<Self as $crate::item::Key>::KEY_BYTES);
+                }
+            }
+        };
+        ($Item:ident, custom(item_store(default))) => {
+            impl $crate::item::LoadOrDefault for $Item {
+                fn load_or_default(storage: &dyn $crate::ReadonlyStorage) -> Self {
+                    <Self as $crate::item::Store>::load(storage).unwrap_or_default()
+                }
+            }
+        };
+        ($Item:ident, custom(item_store(keep_previous))) => {
+            impl $crate::item::KeepPrevious for $Item {}
+        };
+        ($_Item:ident, custom(item_store(key = $_key:literal))) => {};
+        ($_Item:ident, custom(item_store(group = $_group:literal))) => {};
+        ($_Item:ident, custom(item_store($($unknown:tt)*))) => {
+            compile_error!(concat!("unknown `item_store` attribute: `", stringify!($($unknown)*), "`"));
+        };
+        ($_Item:ident, $($_other_meta:tt)+) => {};
+    }
+
+    /// rejects `always` together with `clear`, rather than leaving it to conflicting marker impls
+    #[macro_export]
+    macro_rules! item_store_exclusive_attrs {
+        ($(@seen)?) => {};
+        (@seen [custom(item_store(always))] $($_rest:tt)*) => {
+            compile_error!("`custom(item_store(always))` and `custom(item_store(clear))` are mutually exclusive");
+        };
+        (@seen [custom(item_store(clear))] $($_rest:tt)*) => {
+            compile_error!("`custom(item_store(always))` and `custom(item_store(clear))` are mutually exclusive");
+        };
+        ([custom(item_store(always))] $($rest:tt)*) => {
+            $crate::item_store_exclusive_attrs!(@seen $($rest)*);
+        };
+        ([custom(item_store(clear))] $($rest:tt)*) => {
+            $crate::item_store_exclusive_attrs!(@seen $($rest)*);
+        };
+        ($(@$seen:ident)? [$($_other_meta:tt)+] $($rest:tt)*) => {
+            $crate::item_store_exclusive_attrs!($(@$seen)? $($rest)*);
+        };
+    }
+
+    /// `module::name`, or `module::group::name` with `custom(item_store(group = "group"))`
+    #[macro_export]
+    macro_rules! item_store_default_key {
+        ($name:ident;) => {
+            concat!(module_path!(), "::", stringify!($name))
+        };
+        ($name:ident; [custom(item_store(group = $group:literal))] $($_rest:tt)*) => {
+            concat!(module_path!(), "::", $group, "::", stringify!($name))
+        };
+        ($name:ident; [$($_other_meta:tt)+] $($rest:tt)*) => {
+            $crate::item_store_default_key!($name; $($rest)*)
+        };
+    }
+
+    /// `KEY` from `custom(item_store(key = "..."))` if present, which takes precedence over any
+    /// `group`, otherwise `$default`
+    #[macro_export]
+    macro_rules! item_store_key {
+        ($default:expr;) => {
+            $default
+        };
+        ($default:expr; [custom(item_store(key = $key:literal))] $($_rest:tt)*) => {{
+            assert!(!$key.is_empty(), "`item_store(key = ...)` must not be empty");
+            $key
+        }};
+        ($default:expr; [$($_other_meta:tt)+] $($rest:tt)*) => {
+            $crate::item_store_key!($default; $($rest)*)
+        };
+    }
+
+    /// stores the newtype under its single `item::Key::KEY`, `module::name_inner` unless
+    /// `custom(item_store(key = "..."))` or `group = "..."` is present
+    ///
+    /// ```
+    /// use macro_rules_attribute::derive;
+    /// use newtype_macros::prelude::*;
+    /// use newtype_macros::{ItemStoreImpl, UintNewtypeImpl};
+    ///
+    /// #[derive(Debug, PartialEq, UintNewtypeImpl!, ItemStoreImpl!)]
+    /// #[custom(item_store(key = "height"))]
+    /// struct Height(u64);
+    ///
+    /// let mut storage = MemoryStorage::default();
+    ///
+    /// Height(7).save(&mut storage);
+    ///
+    /// assert_eq!(Height::load(&storage), Some(Height(7)));
+    /// assert_eq!(storage.get(b"height"), Some(7u64.to_be_bytes().to_vec()));
+    /// ```
+    #[macro_export]
+    macro_rules! ItemStoreImpl {
+        (@store $Item:ident $([$($meta_item:tt)+])*) => {
+            const _: fn() = || {
+                fn byte_serde<T: $crate::ByteSerde>() {}
+                byte_serde::<$Item>();
+            };
+
+            $crate::item_store_exclusive_attrs!($([$($meta_item)+])*);
+
+            $(
+                $crate::item_store_derive_attrs!($Item, $($meta_item)+);
+            )*
+        };
+        (
+        $(#[$($meta_item:tt)+])*
+        $pub:vis struct $Item:ident($_field_vis:vis $Inner:ident $(<$($_InnerParam:ty),+>)?);
+    ) => {
+            impl $crate::item::Key for $Item {
+                $crate::paste! {
+                    const KEY: &'static str = $crate::item::build_key($crate::item_store_key!(
+                        $crate::item_store_default_key!([< $Item:snake _ $Inner:snake >]; $([$($meta_item)+])*);
+                        $([$($meta_item)+])*
+                    ));
+                }
+
+            }
+
+            $crate::ItemStoreImpl!(@store $Item $([$($meta_item)+])*);
+        };
+        (
+        $(#[$($meta_item:tt)+])*
+        $pub:vis struct $Item:ident($_field_vis:vis [$Elem:ident; $_len:expr]);
+    ) => {
+            impl $crate::item::Key for $Item {
+                $crate::paste! {
+                    const KEY: &'static str = $crate::item::build_key($crate::item_store_key!(
+                        $crate::item_store_default_key!([< $Item:snake _ $Elem:snake _array >]; $([$($meta_item)+])*);
+                        $([$($meta_item)+])*
+                    ));
+                }
+
+            }
+
+            $crate::ItemStoreImpl!(@store $Item $([$($meta_item)+])*);
+        };
+        (
+        $(#[$($meta_item:tt)+])*
+        $pub:vis enum $Item:ident { $($_body:tt)* }
+    ) => {
+            impl $crate::item::Key for $Item {
+                $crate::paste! {
+                    const KEY: &'static str = $crate::item::build_key($crate::item_store_key!(
+                        $crate::item_store_default_key!([< $Item:snake >]; $([$($meta_item)+])*);
+                        $([$($meta_item)+])*
+                    ));
+                }
+
+            }
+
+            $crate::ItemStoreImpl!(@store $Item $([$($meta_item)+])*);
+        };
+    }
+}
+
+pub mod map {
+    use std::borrow::{Borrow, Cow};
+    use std::net::{IpAddr, Ipv4Addr, Ipv6Addr};
+
+    use crate::{
+        ByteSerde, IterableMutableStorage, IterableStorage, LoadError, MutableStorage,
+        ReadWriteStorage, ReadonlyStorage, StorageError, TryMutableStorage,
+    };
+
+    pub trait IntoMapKey {
+        fn into_map_key(self) -> String;
+    }
+
+    /// by-reference `IntoMapKey`, producing the same key without consuming the value
+    pub trait ToMapKey {
+        fn to_map_key(&self) -> String;
+
+        /// `to_map_key` with `':'` & `'\\'` escaped, so tuple keys split unambiguously
+        fn to_escaped_map_key(&self) -> String {
+            escape_map_key(self.to_map_key().as_str())
+        }
+
+        /// `to_map_key` joining tuple components with `separator` rather than `':'`
+        fn to_map_key_with(&self, _separator: char) -> String {
+            self.to_map_key()
+        }
+    }
+
+    /// backslash-escapes `':'` & `'\\'`
+    pub fn escape_map_key(key: &str) -> String {
+        let mut escaped = String::with_capacity(key.len());
+
+        for c in key.chars() {
+            if matches!(c, ':' | '\\') {
+                escaped.push('\\');
+            }
+
+            escaped.push(c);
+        }
+
+        escaped
+    }
+
+    /// reverses `escape_map_key`, `None` on a dangling or unescaped separator
+    pub fn unescape_map_key(key: &str) -> Option<String> {
+        let mut unescaped = String::with_capacity(key.len());
+        let mut chars = key.chars();
+
+        while let Some(c) = chars.next() {
+            match c {
+                '\\' => unescaped.push(chars.next()?),
+                ':' => return None,
+                c => unescaped.push(c),
+            }
+        }
+
+        Some(unescaped)
+    }
+
+    /// byte offsets of the `':'` separators not escaped by `escape_map_key`
+    fn unescaped_separators(key: &str) -> impl Iterator<Item = usize> + '_ {
+        let mut escaped = false;
+
+        key.char_indices().filter_map(move |(idx, c)| {
+            let separator = !escaped && c == ':';
+            escaped = !escaped && c == '\\';
+            separator.then_some(idx)
+        })
+    }
+
+    impl<T> IntoMapKey for T
+    where
+        T: ToMapKey,
+    {
+        fn into_map_key(self) -> String {
+            self.to_map_key()
+        }
+    }
+
+    pub trait FromMapKey: Sized {
+        fn from_map_key(key: &str) -> Option<Self>;
+
+        /// parses a key produced by `ToMapKey::to_escaped_map_key`
+        fn from_escaped_map_key(key: &str) -> Option<Self> {
+            Self::from_map_key(unescape_map_key(key)?.as_str())
+        }
+
+        /// parses a key produced by `ToMapKey::to_map_key_with`
+        fn from_map_key_with(key: &str, _separator: char) -> Option<Self> {
+            Self::from_map_key(key)
+        }
+    }
+
+    impl<T1, T2> ToMapKey for (T1, T2)
+    where
+        T1: ToMapKey,
+        T2: ToMapKey,
+    {
+        fn to_map_key(&self) -> String {
+            let mut key = self.0.to_map_key();
+            key.push(':');
+            key.push_str(self.1.to_map_key().as_str());
+            key
+        }
+
+        fn to_escaped_map_key(&self) -> String {
+            let mut key = self.0.to_escaped_map_key();
+            key.push(':');
+            key.push_str(self.1.to_escaped_map_key().as_str());
+            key
+        }
+
+        fn to_map_key_with(&self, separator: char) -> String {
+            let mut key = self.0.to_map_key_with(separator);
+            key.push(separator);
+            key.push_str(self.1.to_map_key_with(separator).as_str());
+            key
+        }
+    }
+
+    impl<T1, T2> FromMapKey for (T1, T2)
+    where
+        T1: FromMapKey,
+        T2: FromMapKey,
+    {
+        fn from_map_key(key: &str) -> Option<Self> {
+            // components may themselves contain ':', so try every split point
+            key.match_indices(':').find_map(|(idx, _)| {
+                let t1 = T1::from_map_key(&key[..idx])?;
+                let t2 = T2::from_map_key(&key[idx + 1..])?;
+                Some((t1, t2))
+            })
+        }
+
+        fn from_escaped_map_key(key: &str) -> Option<Self> {
+            unescaped_separators(key).find_map(|idx| {
+                let t1 = T1::from_escaped_map_key(&key[..idx])?;
+                let t2 = T2::from_escaped_map_key(&key[idx + 1..])?;
+                Some((t1, t2))
+            })
+        }
+
+        fn from_map_key_with(key: &str, separator: char) -> Option<Self> {
+            key.match_indices(separator).find_map(|(idx, _)| {
+                let t1 = T1::from_map_key_with(&key[..idx], separator)?;
+                let t2 = T2::from_map_key_with(&key[idx + separator.len_utf8()..], separator)?;
+                Some((t1, t2))
+            })
+        }
+    }
+
+    /// flat tuples key exactly like the right-nested pairs, `(a, b, c)` like `(a, (b, c))`, so
+    /// switching between the two never changes stored keys
+    macro_rules! impl_map_key_tuple {
+        ($Nested:ty => $nested:pat => ($($T:ident $t:ident $idx:tt),+)) => {
+            impl<$($T),+> ToMapKey for ($($T,)+)
+            where
+                $($T: ToMapKey),+
+            {
+                fn to_map_key(&self) -> String {
+                    [$(self.$idx.to_map_key()),+].join(":")
+                }
+
+                fn to_escaped_map_key(&self) -> String {
+                    [$(self.$idx.to_escaped_map_key()),+].join(":")
+                }
+
+                fn to_map_key_with(&self, separator: char) -> String {
+                    [$(self.$idx.to_map_key_with(separator)),+].join(separator.encode_utf8(&mut [0; 4]))
+                }
+            }
+
+            impl<$($T),+> FromMapKey for ($($T,)+)
+            where
+                $($T: FromMapKey),+
+            {
+                fn from_map_key(key: &str) -> Option<Self> {
+                    let $nested = <$Nested>::from_map_key(key)?;
+                    Some(($($t,)+))
+                }
+
+                fn from_escaped_map_key(key: &str) -> Option<Self> {
+                    let $nested = <$Nested>::from_escaped_map_key(key)?;
+                    Some(($($t,)+))
+                }
+
+                fn from_map_key_with(key: &str, separator: char) -> Option<Self> {
+                    let $nested = <$Nested>::from_map_key_with(key, separator)?;
+                    Some(($($t,)+))
+                }
+            }
+        };
+    }
+
+    impl_map_key_tuple!((T1, (T2, T3)) => (t1, (t2, t3)) => (T1 t1 0, T2 t2 1, T3 t3 2));
+    impl_map_key_tuple!(
+        (T1, (T2, (T3, T4))) => (t1, (t2, (t3, t4))) => (T1 t1 0, T2 t2 1, T3 t3 2, T4 t4 3)
+    );
+
+    macro_rules! impl_to_map_key_uint {
+        ($uint:ty) => {
+            impl ToMapKey for $uint {
+                fn to_map_key(&self) -> String {
+                    self.to_string()
+                }
+            }
+
+            impl FromMapKey for $uint {
+                fn from_map_key(key: &str) -> Option<Self> {
+                    key.parse().ok()
+                }
+            }
+        };
+    }
+
+    macro_rules! impl_to_map_key_non_zero {
+        ($nz:ty) => {
+            impl ToMapKey for $nz {
+                fn to_map_key(&self) -> String {
+                    self.get().to_string()
+                }
+            }
+
+            impl FromMapKey for $nz {
+                fn from_map_key(key: &str) -> Option<Self> {
+                    key.parse().ok()
+                }
+            }
+        };
+    }
+
+    impl_to_map_key_uint!(u8);
+    impl_to_map_key_uint!(u16);
+    impl_to_map_key_uint!(u32);
+    impl_to_map_key_uint!(u64);
+    impl_to_map_key_uint!(u128);
+    impl_to_map_key_uint!(usize);
+    impl_to_map_key_non_zero!(std::num::NonZeroU8);
+    impl_to_map_key_non_zero!(std::num::NonZeroU16);
+    impl_to_map_key_non_zero!(std::num::NonZeroU32);
+    impl_to_map_key_non_zero!(std::num::NonZeroU64);
+    impl_to_map_key_non_zero!(std::num::NonZeroU128);
+    impl_to_map_key_non_zero!(std::num::NonZeroUsize);
+
+    impl ToMapKey for String {
+        fn to_map_key(&self) -> String {
+            self.clone()
+        }
+    }
+
+    impl FromMapKey for String {
+        fn from_map_key(key: &str) -> Option<Self> {
+            Some(key.to_owned())
+        }
+    }
+
+    /// `"0"` / `"1"`, the same keys as `0u8` / `1u8`
+    impl ToMapKey for bool {
+        fn to_map_key(&self) -> String {
+            u8::from(*self).to_map_key()
+        }
+    }
+
+    impl FromMapKey for bool {
+        fn from_map_key(key: &str) -> Option<Self> {
+            match key {
+                "0" => Some(false),
+                "1" => Some(true),
+                _ => None,
+            }
+        }
+    }
+
+    /// `"_none"` for `None`, otherwise the inner key with a leading `'_'` doubled, so no inner key
+    /// can produce the sentinel
+    impl<T> ToMapKey for Option<T>
+    where
+        T: ToMapKey,
+    {
+        fn to_map_key(&self) -> String {
+            option_key(self.as_ref().map(T::to_map_key))
+        }
+
+        fn to_escaped_map_key(&self) -> String {
+            option_key(self.as_ref().map(T::to_escaped_map_key))
+        }
+
+        fn to_map_key_with(&self, separator: char) -> String {
+            option_key(self.as_ref().map(|t| t.to_map_key_with(separator)))
+        }
+    }
+
+    impl<T> FromMapKey for Option<T>
+    where
+        T: FromMapKey,
+    {
+        fn from_map_key(key: &str) -> Option<Self> {
+            option_from_key(key, T::from_map_key)
+        }
+
+        fn from_escaped_map_key(key: &str) -> Option<Self> {
+            option_from_key(key, T::from_escaped_map_key)
+        }
+
+        fn from_map_key_with(key: &str, separator: char) -> Option<Self> {
+            option_from_key(key, |key| T::from_map_key_with(key, separator))
+        }
+    }
+
+    const NONE_KEY: &str = "_none";
+
+    fn option_key(key: Option<String>) -> String {
+        match key {
+            None => NONE_KEY.to_owned(),
+            Some(key) if key.starts_with('_') => format!("_{key}"),
+            Some(key) => key,
+        }
+    }
+
+    fn option_from_key<T>(
+        key: &str,
+        from_key: impl FnOnce(&str) -> Option<T>,
+    ) -> Option<Option<T>> {
+        if key == NONE_KEY {
+            return Some(None);
+        }
+
+        let key = match key.strip_prefix('_') {
+            Some(stuffed) if stuffed.starts_with('_') => stuffed,
+            Some(_) => return None,
+            None => key,
+        };
+
+        from_key(key).map(Some)
+    }
+
+    /// the char as a one-char string
+    impl ToMapKey for char {
+        fn to_map_key(&self) -> String {
+            self.to_string()
+        }
+    }
+
+    impl FromMapKey for char {
+        fn from_map_key(key: &str) -> Option<Self> {
+            let mut chars = key.chars();
+
+            match (chars.next(), chars.next()) {
+                (Some(c), None) => Some(c),
+                _ => None,
+            }
+        }
+    }
+
+    /// fixed-width lowercase hex, so keys sort like the addresses and hold no `':'`
+    impl ToMapKey for Ipv4Addr {
+        fn to_map_key(&self) -> String {
+            format!("{:08x}", u32::from(*self))
+        }
+    }
+
+    impl FromMapKey for Ipv4Addr {
+        fn from_map_key(key: &str) -> Option<Self> {
+            from_hex_key(key, 8).map(|bits| Self::from(bits as u32))
+        }
+    }
+
+    /// fixed-width lowercase hex, so keys sort like the addresses and hold no `':'`
+    impl ToMapKey for Ipv6Addr {
+        fn to_map_key(&self) -> String {
+            format!("{:032x}", u128::from(*self))
+        }
+    }
+
+    impl FromMapKey for Ipv6Addr {
+        fn from_map_key(key: &str) -> Option<Self> {
+            from_hex_key(key, 32).map(Self::from)
+        }
+    }
+
+    /// the `Ipv4Addr` or `Ipv6Addr` key, told apart by their width
+    impl ToMapKey for IpAddr {
+        fn to_map_key(&self) -> String {
+            match self {
+                IpAddr::V4(addr) => addr.to_map_key(),
+                IpAddr::V6(addr) => addr.to_map_key(),
+            }
+        }
+    }
+
+    impl FromMapKey for IpAddr {
+        fn from_map_key(key: &str) -> Option<Self> {
+            Ipv4Addr::from_map_key(key)
+                .map(IpAddr::V4)
+                .or_else(|| Ipv6Addr::from_map_key(key).map(IpAddr::V6))
+        }
+    }
+
+    fn from_hex_key(key: &str, width: usize) -> Option<u128> {
+        let is_hex = |b: u8| b.is_ascii_digit() || (b'a'..=b'f').contains(&b);
+
+        if key.len() != width || !key.bytes().all(is_hex) {
+            return None;
+        }
+
+        u128::from_str_radix(key, 16).ok()
+    }
+
+    impl ToMapKey for str {
+        fn to_map_key(&self) -> String {
+            self.to_owned()
+        }
+    }
+
+    /// lowercase hex, so binary keys never contain `':'`
+    impl ToMapKey for [u8] {
+        fn to_map_key(&self) -> String {
+            crate::array::to_hex(self)
+        }
+    }
+
+    /// lowercase hex, so binary keys never contain `':'`
+    impl ToMapKey for Vec<u8> {
+        fn to_map_key(&self) -> String {
+            crate::array::to_hex(self)
+        }
+    }
+
+    /// only the lowercase hex `to_map_key` produces, so every stored key has one `Vec<u8>`
+    impl FromMapKey for Vec<u8> {
+        fn from_map_key(key: &str) -> Option<Self> {
+            if key.bytes().any(|b| b.is_ascii_uppercase()) {
+                return None;
+            }
+
+            crate::array::from_hex(key)
+        }
+    }
+
+    impl<T> ToMapKey for &T
+    where
+        T: ToMapKey + ?Sized,
+    {
+        fn to_map_key(&self) -> String {
+            (**self).to_map_key()
+        }
+
+        fn to_escaped_map_key(&self) -> String {
+            (**self).to_escaped_map_key()
+        }
+
+        fn to_map_key_with(&self, separator: char) -> String {
+            (**self).to_map_key_with(separator)
+        }
+    }
+
+    impl ToMapKey for Cow<'_, str> {
+        fn to_map_key(&self) -> String {
+            self.as_ref().to_owned()
+        }
+    }
+
+    impl FromMapKey for Cow<'_, str> {
+        fn from_map_key(key: &str) -> Option<Self> {
+            Some(Cow::Owned(key.to_owned()))
+        }
+    }
+
+    /// a full map entry key as returned by `map_key`, kept apart from raw `String`s
+    #[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+    pub struct StorageKey(String);
+
+    impl StorageKey {
+        pub fn new(key: String) -> Self {
+            Self(key)
+        }
+
+        pub fn as_str(&self) -> &str {
+            self.0.as_str()
+        }
+
+        pub fn as_bytes(&self) -> &[u8] {
+            self.0.as_bytes()
+        }
+
+        pub fn into_string(self) -> String {
+            self.0
+        }
+    }
+
+    impl std::fmt::Display for StorageKey {
+        fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+            f.write_str(self.as_str())
+        }
+    }
+
+    pub trait MapKeyType {
+        type MapKeyType: ToMapKey + FromMapKey;
+    }
+
+    /// for tuple newtypes whose inner type is `ToMapKey + FromMapKey`, the key is the inner key
+    ///
+    /// ```
+    /// use macro_rules_attribute::derive;
+    /// use newtype_macros::prelude::*;
+    /// use newtype_macros::{MapKeyImpl, MapStoreImpl, StringNewtypeImpl, UintNewtypeImpl};
+    ///
+    /// #[derive(Debug, PartialEq, UintNewtypeImpl!, MapKeyImpl!)]
+    /// struct PoolId(u32);
+    ///
+    /// #[derive(Debug, PartialEq, StringNewtypeImpl!, MapStoreImpl!)]
+    /// #[custom(map_store(key = PoolId))]
+    /// struct PoolName(String);
+    ///
+    /// let mut storage = MemoryStorage::default();
+    ///
+    /// PoolName::new("atom-osmo").save_at(&mut storage, PoolId(1));
+    ///
+    /// assert_eq!(PoolName::load_at(&storage, PoolId(1)), Some(PoolName::new("atom-osmo")));
+    /// ```
+    #[macro_export]
+    macro_rules! MapKeyImpl {
+        (
+        $(#[$($meta_item:tt)+])*
+        $pub:vis struct $Item:ident($_field_vis:vis $Inner:ty);
+    ) => {
+            impl $crate::map::ToMapKey for $Item {
+                fn to_map_key(&self) -> String {
+                    <$Inner as $crate::map::ToMapKey>::to_map_key(&self.0)
+                }
+
+                fn to_escaped_map_key(&self) -> String {
+                    <$Inner as $crate::map::ToMapKey>::to_escaped_map_key(&self.0)
+                }
+
+                fn to_map_key_with(&self, separator: char) -> String {
+                    <$Inner as $crate::map::ToMapKey>::to_map_key_with(&self.0, separator)
+                }
+            }
+
+            impl $crate::map::FromMapKey for $Item {
+                fn from_map_key(key: &str) -> Option<Self> {
+                    <$Inner as $crate::map::FromMapKey>::from_map_key(key).map(Self)
+                }
+
+                fn from_escaped_map_key(key: &str) -> Option<Self> {
+                    <$Inner as $crate::map::FromMapKey>::from_escaped_map_key(key).map(Self)
+                }
+
+                fn from_map_key_with(key: &str, separator: char) -> Option<Self> {
+                    <$Inner as $crate::map::FromMapKey>::from_map_key_with(key, separator).map(Self)
+                }
+            }
+        };
+    }
+
+    /// the storage key prefix of a map, `map::Store` is implemented for every
+    /// `MapKeyType + Prefix + ByteSerde`
+    pub trait Prefix {
+        const KEY_PREFIX: &'static str;
+
+        /// whether keys are written with `ToMapKey::to_escaped_map_key`
+        const ESCAPE_KEYS: bool = false;
+
+        /// whether keys are lowercased with `str::to_lowercase` before they're written, so keys
+        /// differing only in case share an entry, iterated keys come back lowercased
+        ///
+        /// unicode lowercasing isn't locale aware (e.g. Turkish dotted `'İ'`) and a few characters
+        /// change length, so it's best kept to ascii-like identifiers
+        const LOWERCASE_KEYS: bool = false;
+
+        /// joins tuple key components, from `custom(map_store(separator = "/"))`
+        const SEPARATOR: char = ':';
+
+        /// the secondary index entry key to write alongside the encoded value `bytes` at `map_key`,
+        /// from `custom(map_store(index(..)))`, `None` for a map without an index
+        fn index_entry(_bytes: &[u8], _map_key: &StorageKey) -> Option<String> {
+            None
+        }
+    }
+
+    /// the `Prefix::index_entry` override for `custom(map_store(index(..)))`, if present
+    #[macro_export]
+    macro_rules! map_store_index_entry {
+        () => {};
+        ([custom(map_store(index($extractor:ident, $_index:ty)))] $($_rest:tt)*) => {
+            fn index_entry(bytes: &[u8], map_key: &$crate::map::StorageKey) -> Option<String> {
+                let value = <Self as $crate::ByteSerde>::from_owned_bytes(bytes.to_owned());
+
+                Some(Self::index_entry_key(&value.$extractor(), map_key))
+            }
+        };
+        ([$($_other_meta:tt)+] $($rest:tt)*) => {
+            $crate::map_store_index_entry!($($rest)*);
+        };
+    }
+
+    /// writes `bytes` at `map_key` along with the secondary index entry of `T`, if any
+    fn set_at<T>(storage: &mut dyn MutableStorage, map_key: &StorageKey, bytes: &[u8])
+    where
+        T: Prefix,
+    {
+        storage.set_key(map_key, bytes);
+
+        if let Some(index_entry) = T::index_entry(bytes, map_key) {
+            storage.set(index_entry.as_bytes(), map_key.as_bytes());
+        }
+    }
+
+    /// validates a `custom(map_store(separator = "..."))` at compile time, a single ascii
+    /// punctuation character other than `'\\'`, `'_'` & `'-'` (used by escaping, `Option` & signed
+    /// keys), absent from `prefix` and not combined with `escape_keys`
+    pub const fn checked_separator(separator: &str, prefix: &str, escape_keys: bool) -> char {
+        let bytes = separator.as_bytes();
+
+        assert!(
+            bytes.len() == 1
+                && bytes[0].is_ascii_punctuation()
+                && !matches!(bytes[0], b'\\' | b'_' | b'-'),
+            "`map_store(separator = ...)` must be a single ascii punctuation character other than `\\`, `_` or `-`"
+        );
+        assert!(
+            !escape_keys,
+            "`map_store(separator = ...)` can't be combined with `escape_keys`"
+        );
+
+        let prefix = prefix.as_bytes();
+        let mut i = 0;
+
+        while i < prefix.len() {
+            assert!(
+                prefix[i] != bytes[0],
+                "`map_store(separator = ...)` must not appear in the key prefix"
+            );
+            i += 1;
+        }
+
+        bytes[0] as char
+    }
+
+    /// the entries of a `Store::page` and the cursor to the next page
+    pub type Page<T> = (
+        Vec<(<T as MapKeyType>::MapKeyType, T)>,
+        Option<<T as MapKeyType>::MapKeyType>,
+    );
+
+    /// `prefix::key`, the storage key `map::Store` uses for `key` in a map with `KEY_PREFIX = prefix`
+    /// and without `custom(map_store(escape_keys))`
+    pub fn build_key(prefix: &str, key: impl IntoMapKey) -> String {
+        let mut full_key = prefix.to_owned();
+        full_key.push_str("::");
+        full_key.push_str(key.into_map_key().as_str());
+        full_key
+    }
+
+    /// `prefix.idx::`, where `custom(map_store(index(..)))` writes the secondary index of the map
+    /// with `KEY_PREFIX = prefix`
+    pub fn index_key_prefix(prefix: &str) -> String {
+        let mut index_prefix = prefix.to_owned();
+        index_prefix.push_str(".idx::");
+        index_prefix
+    }
+
+    /// `load_at`, `try_load_at` & `save_at` take keys by value or by reference
+    pub trait Store: Sized + MapKeyType {
+        /// `KEY_PREFIX::`, shared by every entry of the map
+        fn map_key_prefix() -> String;
+
+        fn map_key(key: &Self::MapKeyType) -> StorageKey;
+
+        #[must_use]
+        fn load_at(
+            storage: &dyn ReadonlyStorage,
+            key: impl Borrow<Self::MapKeyType>,
+        ) -> Option<Self>;
+
+        fn try_load_at(
+            storage: &dyn ReadonlyStorage,
+            key: impl Borrow<Self::MapKeyType>,
+        ) -> Result<Option<Self>, LoadError>;
+
+        fn save_at(&self, storage: &mut dyn MutableStorage, key: impl Borrow<Self::MapKeyType>);
+
+        /// `save_at` through a backend whose writes can fail
+        fn try_save_at(
+            &self,
+            storage: &mut dyn TryMutableStorage,
+            key: impl Borrow<Self::MapKeyType>,
+        ) -> Result<(), StorageError>;
+
+        #[must_use]
+        fn load_at_ref(storage: &dyn ReadonlyStorage, key: &Self::MapKeyType) -> Option<Self>;
+
+        fn save_at_ref(&self, storage: &mut dyn MutableStorage, key: &Self::MapKeyType);
+
+        /// whether anything is stored at `key`, without decoding it
+        fn has_at(storage: &dyn ReadonlyStorage, key: impl Borrow<Self::MapKeyType>) -> bool;
+
+        /// saves only if nothing is stored at `key`, returns whether it saved
+        fn save_if_absent(
+            &self,
+            storage: &mut dyn ReadWriteStorage,
+            key: impl Borrow<Self::MapKeyType>,
+        ) -> bool;
+
+        /// the entry at `key`, loaded once and written back when the `Entry` drops if it changed
+        fn entry(
+            storage: &mut dyn ReadWriteStorage,
+            key: impl Borrow<Self::MapKeyType>,
+        ) -> Entry<'_, Self>;
+
+        /// loads at every key with one `ReadonlyStorage::get_many`, in the order of `keys`
+        fn load_many<K>(storage: &dyn ReadonlyStorage, keys: &[K]) -> Vec<Option<Self>>
+        where
+            K: Borrow<Self::MapKeyType>;
+
+        /// loads at `key`, or returns `f()` without saving it if nothing is stored there
+        fn load_at_or_else<F>(
+            storage: &dyn ReadonlyStorage,
+            key: impl Borrow<Self::MapKeyType>,
+            f: F,
+        ) -> Self
+        where
+            F: FnOnce() -> Self;
+
+        /// loads at `key`, or saves and returns `f()` if nothing is stored there
+        fn get_or_insert_with<F>(
+            storage: &mut dyn ReadWriteStorage,
+            key: impl Borrow<Self::MapKeyType>,
+            f: F,
+        ) -> Self
+        where
+            F: FnOnce() -> Self;
+
+        /// saves `f` applied to the value at `key` if there is one, returns whether there was
+        fn modify_at<F>(
+            storage: &mut dyn ReadWriteStorage,
+            key: impl Borrow<Self::MapKeyType>,
+            f: F,
+        ) -> bool
+        where
+            F: FnOnce(Self) -> Self;
+
+        /// the key of a full storage key string, `None` for another map's key or an invalid key
+        fn parse_key(full_key: &str) -> Option<Self::MapKeyType>;
+
+        /// moves the value at `from` to `to`, overwriting it, returns false & does nothing if
+        /// nothing is stored at `from`
+        fn move_at(
+            storage: &mut dyn ReadWriteStorage,
+            from: Self::MapKeyType,
+            to: Self::MapKeyType,
+        ) -> bool;
+
+        /// exchanges the values at `a` & `b`, either may be empty
+        fn swap_at(storage: &mut dyn ReadWriteStorage, a: Self::MapKeyType, b: Self::MapKeyType);
+
+        /// moves the raw bytes stored under `old_key` to `key`, without decoding them
+        fn migrate_key_at(
+            storage: &mut dyn ReadWriteStorage,
+            old_key: &[u8],
+            key: Self::MapKeyType,
+        );
+
+        fn iter(
+            storage: &dyn IterableStorage,
+        ) -> impl Iterator<Item = (Self::MapKeyType, Self)> + '_;
+
+        /// entries whose tuple key starts with the `partial` components, e.g. `5` for every `(5, _)`
+        fn prefix_scan(
+            storage: &dyn IterableStorage,
+            partial: impl ToMapKey,
+        ) -> impl Iterator<Item = (Self::MapKeyType, Self)> + '_;
+
+        /// only decodes the keys
+        fn iter_keys(storage: &dyn IterableStorage) -> impl Iterator<Item = Self::MapKeyType> + '_;
+
+        /// only decodes the values
+        fn iter_values(storage: &dyn IterableStorage) -> impl Iterator<Item = Self> + '_;
+
+        /// the entry with the smallest key, decodes every key but only that value, as keys don't
+        /// sort like their storage keys, e.g. `10` before `9`
+        fn first(storage: &dyn IterableStorage) -> Option<(Self::MapKeyType, Self)>
+        where
+            Self::MapKeyType: Ord;
+
+        /// the entry with the largest key, decodes every key but only that value
+        fn last(storage: &dyn IterableStorage) -> Option<(Self::MapKeyType, Self)>
+        where
+            Self::MapKeyType: Ord;
+
+        /// every entry, ordered by key rather than storage key
+        fn to_btree_map(
+            storage: &dyn IterableStorage,
+        ) -> std::collections::BTreeMap<Self::MapKeyType, Self>
+        where
+            Self::MapKeyType: Ord;
+
+        /// up to `limit` entries in key order strictly after `start_after`, and the cursor to pass as
+        /// the next `start_after`, `None` once there are no entries left, a `limit` of zero returns
+        /// `start_after` unchanged, decodes every key but only the values of the page
+        fn page(
+            storage: &dyn IterableStorage,
+            start_after: Option<Self::MapKeyType>,
+            limit: usize,
+        ) -> Page<Self>
+        where
+            Self::MapKeyType: Ord;
+
+        /// whether anything is stored, stops at the first entry rather than counting them all
+        fn exists_any(storage: &dyn IterableStorage) -> bool;
+
+        /// number of stored entries, decodes neither keys nor values
+        fn count(storage: &dyn IterableStorage) -> usize;
+
+        /// summed byte length of every stored key & value, decodes neither
+        fn total_bytes(storage: &dyn IterableStorage) -> usize;
+
+        /// rewrites the raw bytes of every stored value in place, without decoding them
+        fn migrate_values<F>(storage: &mut dyn IterableMutableStorage, reencode: F)
+        where
+            F: FnMut(Vec<u8>) -> Vec<u8>;
+
+        /// clears every entry for which `f` returns `false`, takes `IterableMutableStorage` as the
+        /// entries are iterated before any are cleared
+        fn retain<F>(storage: &mut dyn IterableMutableStorage, f: F)
+        where
+            F: FnMut(&Self::MapKeyType, &Self) -> bool;
+    }
+
+    fn key_from_map_key<T>(full_key: &[u8]) -> T::MapKeyType
+    where
+        T: Store,
+    {
+        std::str::from_utf8(full_key)
+            .ok()
+            .and_then(T::parse_key)
+            .expect("stored valid map key")
+    }
+
+    impl<T> Store for T
+    where
+        T: MapKeyType + Prefix + ByteSerde,
+    {
+        fn map_key_prefix() -> String {
+            build_key(Self::KEY_PREFIX, "")
+        }
+
+        fn map_key(key: &Self::MapKeyType) -> StorageKey {
+            let mut key = if Self::ESCAPE_KEYS {
+                key.to_escaped_map_key()
+            } else {
+                key.to_map_key_with(Self::SEPARATOR)
+            };
+
+            if Self::LOWERCASE_KEYS {
+                key = key.to_lowercase();
+            }
+
+            StorageKey::new(build_key(Self::KEY_PREFIX, key))
+        }
+
+        fn load_at(
+            storage: &dyn ReadonlyStorage,
+            key: impl Borrow<Self::MapKeyType>,
+        ) -> Option<Self> {
+            storage
+                .get_key(&Self::map_key(key.borrow()))
+                .map(T::from_owned_bytes)
+        }
+
+        fn try_load_at(
+            storage: &dyn ReadonlyStorage,
+            key: impl Borrow<Self::MapKeyType>,
+        ) -> Result<Option<Self>, LoadError> {
+            storage
+                .get_key(&Self::map_key(key.borrow()))
+                .map(T::try_from_owned_bytes)
+                .transpose()
+        }
+
+        fn save_at(&self, storage: &mut dyn MutableStorage, key: impl Borrow<Self::MapKeyType>) {
+            set_at::<T>(
+                storage,
+                &Self::map_key(key.borrow()),
+                self.to_owned_bytes().as_slice(),
+            );
+        }
+
+        fn try_save_at(
+            &self,
+            storage: &mut dyn TryMutableStorage,
+            key: impl Borrow<Self::MapKeyType>,
+        ) -> Result<(), StorageError> {
+            let map_key = Self::map_key(key.borrow());
+            let bytes = self.to_owned_bytes();
+
+            storage.try_set(map_key.as_bytes(), bytes.as_slice())?;
+
+            if let Some(index_entry) = T::index_entry(&bytes, &map_key) {
+                storage.try_set(index_entry.as_bytes(), map_key.as_bytes())?;
+            }
+
+            Ok(())
+        }
+
+        fn load_at_ref(storage: &dyn ReadonlyStorage, key: &Self::MapKeyType) -> Option<Self> {
+            Self::load_at(storage, key)
+        }
+
+        fn save_at_ref(&self, storage: &mut dyn MutableStorage, key: &Self::MapKeyType) {
+            self.save_at(storage, key)
+        }
+
+        fn has_at(storage: &dyn ReadonlyStorage, key: impl Borrow<Self::MapKeyType>) -> bool {
+            storage.get_key(&Self::map_key(key.borrow())).is_some()
+        }
+
+        fn save_if_absent(
+            &self,
+            storage: &mut dyn ReadWriteStorage,
+            key: impl Borrow<Self::MapKeyType>,
+        ) -> bool {
+            let map_key = Self::map_key(key.borrow());
+
+            if storage.get_key(&map_key).is_some() {
+                return false;
+            }
+
+            set_at::<T>(storage, &map_key, self.to_owned_bytes().as_slice());
+
+            true
+        }
+
+        fn entry(
+            storage: &mut dyn ReadWriteStorage,
+            key: impl Borrow<Self::MapKeyType>,
+        ) -> Entry<'_, Self> {
+            let key = Self::map_key(key.borrow());
+            let value = storage.get_key(&key).map(T::from_owned_bytes);
+
+            Entry {
+                storage,
+                key,
+                value,
+                dirty: false,
+                write: |storage, key, value| set_at::<T>(storage, key, &value.to_owned_bytes()),
+            }
+        }
+
+        fn load_many<K>(storage: &dyn ReadonlyStorage, keys: &[K]) -> Vec<Option<Self>>
+        where
+            K: Borrow<Self::MapKeyType>,
+        {
+            let keys: Vec<_> = keys.iter().map(|key| Self::map_key(key.borrow())).collect();
+            let keys: Vec<_> = keys.iter().map(StorageKey::as_bytes).collect();
+
+            storage
+                .get_many(&keys)
+                .into_iter()
+                .map(|bytes| bytes.map(T::from_owned_bytes))
+                .collect()
+        }
+
+        fn load_at_or_else<F>(
+            storage: &dyn ReadonlyStorage,
+            key: impl Borrow<Self::MapKeyType>,
+            f: F,
+        ) -> Self
+        where
+            F: FnOnce() -> Self,
+        {
+            Self::load_at(storage, key).unwrap_or_else(f)
+        }
+
+        fn get_or_insert_with<F>(
+            storage: &mut dyn ReadWriteStorage,
+            key: impl Borrow<Self::MapKeyType>,
+            f: F,
+        ) -> Self
+        where
+            F: FnOnce() -> Self,
+        {
+            let map_key = Self::map_key(key.borrow());
+
+            if let Some(bytes) = storage.get_key(&map_key) {
+                return T::from_owned_bytes(bytes);
+            }
+
+            let value = f();
+
+            set_at::<T>(storage, &map_key, value.to_owned_bytes().as_slice());
+
+            value
+        }
+
+        fn modify_at<F>(
+            storage: &mut dyn ReadWriteStorage,
+            key: impl Borrow<Self::MapKeyType>,
+            f: F,
+        ) -> bool
+        where
+            F: FnOnce(Self) -> Self,
+        {
+            let map_key = Self::map_key(key.borrow());
+
+            let Some(bytes) = storage.get_key(&map_key) else {
+                return false;
+            };
+
+            let value = f(T::from_owned_bytes(bytes));
+
+            set_at::<T>(storage, &map_key, value.to_owned_bytes().as_slice());
+
+            true
+        }
+
+        fn parse_key(full_key: &str) -> Option<Self::MapKeyType> {
+            let key = full_key.strip_prefix(Self::map_key_prefix().as_str())?;
+
+            if Self::ESCAPE_KEYS {
+                FromMapKey::from_escaped_map_key(key)
+            } else {
+                FromMapKey::from_map_key_with(key, Self::SEPARATOR)
+            }
+        }
+
+        fn move_at(
+            storage: &mut dyn ReadWriteStorage,
+            from: Self::MapKeyType,
+            to: Self::MapKeyType,
+        ) -> bool {
+            let from = Self::map_key(&from);
+
+            let Some(bytes) = storage.get_key(&from) else {
+                return false;
+            };
+
+            let to = Self::map_key(&to);
+
+            if from != to {
+                set_at::<T>(storage, &to, bytes.as_slice());
+                storage.clear_key(&from);
+            }
+
+            true
+        }
+
+        fn swap_at(storage: &mut dyn ReadWriteStorage, a: Self::MapKeyType, b: Self::MapKeyType) {
+            let (a, b) = (Self::map_key(&a), Self::map_key(&b));
+
+            let (a_bytes, b_bytes) = (storage.get_key(&a), storage.get_key(&b));
+
+            for (key, bytes) in [(a, b_bytes), (b, a_bytes)] {
+                match bytes {
+                    Some(bytes) => set_at::<T>(storage, &key, bytes.as_slice()),
+                    None => storage.clear_key(&key),
+                }
+            }
+        }
+
+        fn migrate_key_at(
+            storage: &mut dyn ReadWriteStorage,
+            old_key: &[u8],
+            key: Self::MapKeyType,
+        ) {
+            let key = Self::map_key(&key);
+
+            if old_key == key.as_bytes() {
+                return;
+            }
+
+            let Some(bytes) = storage.get(old_key) else {
+                return;
+            };
+
+            set_at::<T>(storage, &key, bytes.as_slice());
+            storage.clear(old_key);
+        }
+
+        fn iter(
+            storage: &dyn IterableStorage,
+        ) -> impl Iterator<Item = (Self::MapKeyType, Self)> + '_ {
+            storage
+                .prefix_iter(Self::map_key_prefix().as_bytes())
+                .map(|(key, value)| (key_from_map_key::<T>(&key), T::from_owned_bytes(value)))
+        }
+
+        fn prefix_scan(
+            storage: &dyn IterableStorage,
+            partial: impl ToMapKey,
+        ) -> impl Iterator<Item = (Self::MapKeyType, Self)> + '_ {
+            let mut partial = if Self::ESCAPE_KEYS {
+                partial.to_escaped_map_key()
+            } else {
+                partial.to_map_key_with(Self::SEPARATOR)
+            };
+
+            if Self::LOWERCASE_KEYS {
+                partial = partial.to_lowercase();
+            }
+
+            let mut prefix = build_key(Self::KEY_PREFIX, partial);
+            prefix.push(Self::SEPARATOR);
+
+            storage
+                .prefix_iter(prefix.as_bytes())
+                .map(|(key, value)| (key_from_map_key::<T>(&key), T::from_owned_bytes(value)))
+        }
+
+        fn iter_keys(storage: &dyn IterableStorage) -> impl Iterator<Item = Self::MapKeyType> + '_ {
+            storage
+                .prefix_iter(Self::map_key_prefix().as_bytes())
+                .map(|(key, _)| key_from_map_key::<T>(&key))
+        }
+
+        fn iter_values(storage: &dyn IterableStorage) -> impl Iterator<Item = Self> + '_ {
+            storage
+                .prefix_iter(Self::map_key_prefix().as_bytes())
+                .map(|(_, value)| T::from_owned_bytes(value))
+        }
+
+        fn first(storage: &dyn IterableStorage) -> Option<(Self::MapKeyType, Self)>
+        where
+            Self::MapKeyType: Ord,
+        {
+            storage
+                .prefix_iter(Self::map_key_prefix().as_bytes())
+                .map(|(key, value)| (key_from_map_key::<T>(&key), value))
+                .min_by(|(a, _), (b, _)| a.cmp(b))
+                .map(|(key, value)| (key, T::from_owned_bytes(value)))
+        }
+
+        fn last(storage: &dyn IterableStorage) -> Option<(Self::MapKeyType, Self)>
+        where
+            Self::MapKeyType: Ord,
+        {
+            storage
+                .prefix_iter(Self::map_key_prefix().as_bytes())
+                .map(|(key, value)| (key_from_map_key::<T>(&key), value))
+                .max_by(|(a, _), (b, _)| a.cmp(b))
+                .map(|(key, value)| (key, T::from_owned_bytes(value)))
+        }
+
+        fn to_btree_map(
+            storage: &dyn IterableStorage,
+        ) -> std::collections::BTreeMap<Self::MapKeyType, Self>
+        where
+            Self::MapKeyType: Ord,
+        {
+            Self::iter(storage).collect()
+        }
+
+        fn page(
+            storage: &dyn IterableStorage,
+            start_after: Option<Self::MapKeyType>,
+            limit: usize,
+        ) -> Page<Self>
+        where
+            Self::MapKeyType: Ord,
+        {
+            if limit == 0 {
+                return (Vec::new(), start_after);
+            }
+
+            let mut entries: Vec<_> = storage
+                .prefix_iter(Self::map_key_prefix().as_bytes())
+                .map(|(raw_key, value)| (key_from_map_key::<T>(&raw_key), raw_key, value))
+                .filter(|(key, ..)| {
+                    start_after
+                        .as_ref()
+                        .is_none_or(|start_after| key > start_after)
+                })
+                .collect();
+
+            entries.sort_by(|(a, ..), (b, ..)| a.cmp(b));
+
+            let more = entries.len() > limit;
+            entries.truncate(limit);
+
+            let cursor = entries
+                .last()
+                .filter(|_| more)
+                .map(|(_, raw_key, _)| key_from_map_key::<T>(raw_key));
+
+            let page = entries
+                .into_iter()
+                .map(|(key, _, value)| (key, T::from_owned_bytes(value)))
+                .collect();
+
+            (page, cursor)
+        }
+
+        fn exists_any(storage: &dyn IterableStorage) -> bool {
+            storage
+                .prefix_iter(Self::map_key_prefix().as_bytes())
+                .next()
+                .is_some()
+        }
+
+        fn count(storage: &dyn IterableStorage) -> usize {
+            storage
+                .prefix_iter(Self::map_key_prefix().as_bytes())
+                .count()
+        }
+
+        fn total_bytes(storage: &dyn IterableStorage) -> usize {
+            storage
+                .prefix_iter(Self::map_key_prefix().as_bytes())
+                .map(|(key, value)| key.len() + value.len())
+                .sum()
+        }
+
+        fn migrate_values<F>(storage: &mut dyn IterableMutableStorage, mut reencode: F)
+        where
+            F: FnMut(Vec<u8>) -> Vec<u8>,
+        {
+            let entries: Vec<_> = storage
+                .prefix_iter(Self::map_key_prefix().as_bytes())
+                .collect();
+
+            for (key, value) in entries {
+                storage.set(&key, reencode(value).as_slice());
+            }
+        }
+
+        fn retain<F>(storage: &mut dyn IterableMutableStorage, mut f: F)
+        where
+            F: FnMut(&Self::MapKeyType, &Self) -> bool,
+        {
+            let rejected: Vec<_> = storage
+                .prefix_iter(Self::map_key_prefix().as_bytes())
+                .filter_map(|(key, value)| {
+                    let keep = f(&key_from_map_key::<T>(&key), &T::from_owned_bytes(value));
+
+                    (!keep).then_some(key)
+                })
+                .collect();
+
+            for key in rejected {
+                storage.clear(&key);
+            }
+        }
+    }
+
+    /// a map entry from `Store::entry`, changes are written back when it drops or on `save`
+    pub struct Entry<'a, T> {
+        storage: &'a mut dyn ReadWriteStorage,
+        key: StorageKey,
+        value: Option<T>,
+        dirty: bool,
+        write: fn(&mut dyn MutableStorage, &StorageKey, &T),
+    }
+
+    impl<T> Entry<'_, T> {
+        pub fn get(&self) -> Option<&T> {
+            self.value.as_ref()
+        }
+
+        pub fn or_insert(self, value: T) -> Self {
+            self.or_insert_with(|| value)
+        }
+
+        pub fn or_insert_with<F>(mut self, f: F) -> Self
+        where
+            F: FnOnce() -> T,
+        {
+            if self.value.is_none() {
+                self.value = Some(f());
+                self.dirty = true;
+            }
+
+            self
+        }
+
+        pub fn and_modify<F>(mut self, f: F) -> Self
+        where
+            F: FnOnce(&mut T),
+        {
+            if let Some(value) = self.value.as_mut() {
+                f(value);
+                self.dirty = true;
+            }
+
+            self
+        }
+
+        /// writes back now rather than when the entry drops
+        pub fn save(self) {}
+    }
+
+    impl<T> Drop for Entry<'_, T> {
+        fn drop(&mut self) {
+            if !self.dirty {
+                return;
+            }
+
+            if let Some(value) = &self.value {
+                (self.write)(&mut *self.storage, &self.key, value);
+            }
+        }
+    }
+
+    pub trait ClearAt: MapKeyType {
+        fn clear_at(storage: &mut dyn MutableStorage, key: Self::MapKeyType);
+
+        /// clears every entry of the map along with any secondary index, O(n) in the number of entries
+        fn clear_all(storage: &mut dyn IterableMutableStorage);
+    }
+
+    pub trait LoadAlwaysAt: Sized + MapKeyType {
+        fn load_always_at(storage: &dyn ReadonlyStorage, key: Self::MapKeyType) -> Self;
+    }
+
+    pub trait ClearAtOrLoadAlwaysAt {}
+
+    /// per-key counters over uint newtypes, from `custom(map_store(counter))`, absent keys count
+    /// from zero
+    pub trait Counter: Store {
+        type Count;
+
+        /// returns the new count, overflow panics, a count rejected by
+        /// `custom(uint_newtype(validate))` is an error and isn't written
+        fn increment_at(
+            storage: &mut dyn ReadWriteStorage,
+            key: Self::MapKeyType,
+        ) -> Result<Self::Count, crate::ValidationError>;
+
+        /// returns the new count, `None` without writing if the count is absent or zero, a count
+        /// rejected by `custom(uint_newtype(validate))` is an error and isn't written
+        fn decrement_at(
+            storage: &mut dyn ReadWriteStorage,
+            key: Self::MapKeyType,
+        ) -> Result<Option<Self::Count>, crate::ValidationError>;
+    }
+
+    /// maps keyed by `(primary, secondary)`, iterable under a fixed primary
+    pub trait DoubleMap<K1, K2>: Store<MapKeyType = (K1, K2)> {
+        /// every `(secondary, value)` stored under `primary`, in storage key order
+        fn iter_prefix(
+            storage: &dyn IterableStorage,
+            primary: K1,
+        ) -> impl Iterator<Item = (K2, Self)> + '_;
+    }
+
+    impl<T, K1, K2> DoubleMap<K1, K2> for T
+    where
+        T: Store<MapKeyType = (K1, K2)>,
+        K1: ToMapKey,
+    {
+        fn iter_prefix(
+            storage: &dyn IterableStorage,
+            primary: K1,
+        ) -> impl Iterator<Item = (K2, Self)> + '_ {
+            T::prefix_scan(storage, primary).map(|((_, secondary), value)| (secondary, value))
+        }
+    }
+
+    /// secondary index over a map, entries are written to `KEY_PREFIX.idx::<secondary>::<primary>`
+    /// so they stay out of the `KEY_PREFIX::` value namespace, the secondary is escaped so one
+    /// secondary is never a prefix of another's entries
+    ///
+    /// every `Store` write also writes the entry of the written value, plain `save_at` & `clear_at`
+    /// can't read the value they replace so its entry stays behind until `load_by_index` skips it,
+    /// `save_indexed_at` & `clear_indexed_at` remove it
+    pub trait IndexedStore: Store {
+        type IndexKeyType;
+
+        fn index_key(&self) -> Self::IndexKeyType;
+
+        /// `save_at`, also removing the index entry of the value stored there before
+        fn save_indexed_at(&self, storage: &mut dyn ReadWriteStorage, key: Self::MapKeyType);
+
+        /// clears at `key` along with the index entries of the stored and the current secondary value
+        fn clear_indexed_at(&self, storage: &mut dyn ReadWriteStorage, key: Self::MapKeyType);
+
+        /// primary keys whose stored value has `secondary`, in storage key order
+        fn load_by_index(
+            storage: &dyn IterableStorage,
+            secondary: Self::IndexKeyType,
+        ) -> Vec<Self::MapKeyType>;
+    }
+
+    /// a set of values, each stored under its own key and encoded as its own value so the rest of
+    /// `Store` reads it back, from `custom(map_store(self_key))`
+    pub trait SelfKeyed: Store<MapKeyType = Self> {
+        /// adds `self` to the set
+        fn save(&self, storage: &mut dyn MutableStorage);
+
+        fn contains(storage: &dyn ReadonlyStorage, value: &Self) -> bool;
+
+        /// removes `self` from the set
+        fn remove(&self, storage: &mut dyn MutableStorage);
+    }
+
+    #[macro_export]
+    macro_rules! store_map_derive_attrs {
+        ($Item:ident, custom(map_store(key, $key:ty))) => {
+            impl $crate::map::MapKeyType for $Item {
+                type MapKeyType = $key;
+            }
+        };
+        ($Item:ident, custom(map_store(always))) => {
+            impl $crate::map::ClearAtOrLoadAlwaysAt for $Item {}
+
+            impl $crate::map::LoadAlwaysAt for $Item {
+                fn load_always_at(
+                    storage: &dyn $crate::ReadonlyStorage,
+                    key: Self::MapKeyType,
+                ) -> Self {
+                    <Self as $crate::map::Store>::load_at(storage, &key).unwrap_or_else(|| {
+                        let map_key = <Self as $crate::map::Store>::map_key(&key);
+
+                        panic!("always present in storage, missing `{map_key}`")
+                    })
+                }
+            }
+        };
+        ($Item:ident, custom(map_store(clear))) => {
+            impl $crate::map::ClearAtOrLoadAlwaysAt for $Item {}
+
+            impl $crate::map::ClearAt for $Item {
+                fn clear_at(storage: &mut dyn $crate::MutableStorage, key: Self::MapKeyType) {
+                    storage.clear_key(&<Self as $crate::map::Store>::map_key(&key));
+                }
+
+                fn clear_all(storage: &mut dyn $crate::IterableMutableStorage) {
+                    let index_prefix = $crate::map::index_key_prefix(<Self as $crate::map::Prefix>::KEY_PREFIX);
+
+                    let keys: Vec<_> = storage
+                        .prefix_iter(<Self as $crate::map::Store>::map_key_prefix().as_bytes())
+                        .chain(storage.prefix_iter(index_prefix.as_bytes()))
+                        .map(|(key, _)| key)
+                        .collect();
+
+                    for key in keys {
+                        storage.clear(&key);
+                    }
+                }
+            }
+        };
+        ($Item:ident, custom(map_store(counter))) => {
+            impl $crate::map::Counter for $Item {
+                type Count = <Self as $crate::uint::Newtype>::PrimitiveInner;
+
+                fn increment_at(
+                    storage: &mut dyn $crate::ReadWriteStorage,
+                    key: Self::MapKeyType,
+                ) -> Result<Self::Count, $crate::ValidationError> {
+                    let count = <Self as $crate::map::Store>::load_at(storage, &key)
+                        .map_or(0, |count| count.0)
+                        .checked_add(1)
+                        .expect("counter overflow");
+
+                    let value = Self(count);
+
+                    value.check_valid()?;
+
+                    <Self as $crate::map::Store>::save_at(&value, storage, key);
+
+                    Ok(count)
+                }
+
+                fn decrement_at(
+                    storage: &mut dyn $crate::ReadWriteStorage,
+                    key: Self::MapKeyType,
+                ) -> Result<Option<Self::Count>, $crate::ValidationError> {
+                    let Some(count) = <Self as $crate::map::Store>::load_at(storage, &key)
+                        .and_then(|count| count.0.checked_sub(1))
+                    else {
+                        return Ok(None);
+                    };
+
+                    let value = Self(count);
+
+                    value.check_valid()?;
+
+                    <Self as $crate::map::Store>::save_at(&value, storage, key);
+
+                    Ok(Some(count))
+                }
+            }
+        };
+        ($Item:ident, custom(map_store(index($extractor:ident, $index:ty)))) => {
+            impl $Item {
+                fn index_prefix(secondary: &$index) -> String {
+                    let mut prefix = $crate::map::index_key_prefix(<Self as $crate::map::Prefix>::KEY_PREFIX);
+                    prefix.push_str($crate::map::ToMapKey::to_escaped_map_key(secondary).as_str());
+                    prefix.push_str("::");
+                    prefix
+                }
+
+                fn index_entry_key(secondary: &$index, map_key: &$crate::map::StorageKey) -> String {
+                    let primary_len = <Self as $crate::map::Store>::map_key_prefix().len();
+
+                    let mut index_key = Self::index_prefix(secondary);
+                    index_key.push_str(&map_key.as_str()[primary_len..]);
+                    index_key
+                }
+            }
+
+            impl $crate::map::IndexedStore for $Item {
+                type IndexKeyType = $index;
+
+                fn index_key(&self) -> Self::IndexKeyType {
+                    self.$extractor()
+                }
+
+                fn save_indexed_at(
+                    &self,
+                    storage: &mut dyn $crate::ReadWriteStorage,
+                    key: Self::MapKeyType,
+                ) {
+                    let map_key = <Self as $crate::map::Store>::map_key(&key);
+
+                    if let Some(previous) = <Self as $crate::map::Store>::load_at(storage, &key) {
+                        storage.clear(Self::index_entry_key(&previous.index_key(), &map_key).as_bytes());
+                    }
+
+                    <Self as $crate::map::Store>::save_at(self, storage, &key);
+                }
+
+                fn clear_indexed_at(
+                    &self,
+                    storage: &mut dyn $crate::ReadWriteStorage,
+                    key: Self::MapKeyType,
+                ) {
+                    let map_key = <Self as $crate::map::Store>::map_key(&key);
+
+                    if let Some(stored) = <Self as $crate::map::Store>::load_at(storage, &key) {
+                        storage.clear(Self::index_entry_key(&stored.index_key(), &map_key).as_bytes());
+                    }
+
+                    storage.clear(Self::index_entry_key(&self.index_key(), &map_key).as_bytes());
+
+                    storage.clear_key(&map_key);
+                }
+
+                fn load_by_index(
+                    storage: &dyn $crate::IterableStorage,
+                    secondary: Self::IndexKeyType,
+                ) -> Vec<Self::MapKeyType> {
+                    storage
+                        .prefix_iter(Self::index_prefix(&secondary).as_bytes())
+                        .filter_map(|(index_entry, map_key)| {
+                            let key = std::str::from_utf8(&map_key)
+                                .ok()
+                                .and_then(<Self as $crate::map::Store>::parse_key)
+                                .expect("stored valid primary key");
+
+                            // entries left behind by a plain `clear_at` or by a save that changed
+                            // the secondary no longer match the stored value
+                            let stored = <Self as $crate::map::Store>::load_at(storage, &key)?;
+                            let map_key = <Self as $crate::map::Store>::map_key(&key);
+
+                            (Self::index_entry_key(&stored.index_key(), &map_key).as_bytes()
+                                == index_entry.as_slice())
+                            .then_some(key)
+                        })
+                        .collect()
+                }
+            }
+        };
+        ($Item:ident, custom(map_store(self_key))) => {
+            impl $crate::map::MapKeyType for $Item {
+                type MapKeyType = $Item;
+            }
+
+            impl $crate::map::SelfKeyed for $Item {
+                fn save(&self, storage: &mut dyn $crate::MutableStorage) {
+                    <Self as $crate::map::Store>::save_at_ref(self, storage, self);
+                }
+
+                fn contains(storage: &dyn $crate::ReadonlyStorage, value: &Self) -> bool {
+                    <Self as $crate::map::Store>::has_at(storage, value)
+                }
+
+                fn remove(&self, storage: &mut dyn $crate::MutableStorage) {
+                    storage.clear_key(&<Self as $crate::map::Store>::map_key(self));
+                }
+            }
+        };
+        ($_Item:ident, custom(map_store(key))) => {
+            compile_error!("`custom(map_store(key))` is missing the key type, e.g. `custom(map_store(key, u64))`");
+        };
+        ($_Item:ident, custom(map_store(prefix = $_prefix:literal))) => {};
+        ($_Item:ident, custom(map_store(escape_keys))) => {};
+        ($_Item:ident, custom(map_store(lowercase_keys))) => {};
+        ($_Item:ident, custom(map_store(separator = $_separator:literal))) => {};
+        ($_Item:ident, custom(map_store($($unknown:tt)*))) => {
+            compile_error!(concat!("unknown `map_store` attribute: `", stringify!($($unknown)*), "`"));
+        };
+        ($_Item:ident, $($_other_meta:tt)+) => {};
+    }
+
+    /// rejects `always` together with `clear`, rather than leaving it to conflicting marker impls
+    #[macro_export]
+    macro_rules! map_store_exclusive_attrs {
+        ($(@seen)?) => {};
+        (@seen [custom(map_store(always))] $($_rest:tt)*) => {
+            compile_error!("`custom(map_store(always))` and `custom(map_store(clear))` are mutually exclusive");
+        };
+        (@seen [custom(map_store(clear))] $($_rest:tt)*) => {
+            compile_error!("`custom(map_store(always))` and `custom(map_store(clear))` are mutually exclusive");
+        };
+        ([custom(map_store(always))] $($rest:tt)*) => {
+            $crate::map_store_exclusive_attrs!(@seen $($rest)*);
+        };
+        ([custom(map_store(clear))] $($rest:tt)*) => {
+            $crate::map_store_exclusive_attrs!(@seen $($rest)*);
+        };
+        ($(@$seen:ident)? [$($_other_meta:tt)+] $($rest:tt)*) => {
+            $crate::map_store_exclusive_attrs!($(@$seen)? $($rest)*);
+        };
+    }
+
+    /// requires a `custom(map_store(key, ...))` or `custom(map_store(self_key))` attribute
+    #[macro_export]
+    macro_rules! map_store_key_attr {
+        ($_Item:ident;) => {
+            compile_error!("`MapStoreImpl!` requires a `#[custom(map_store(key, KeyType))]` or `#[custom(map_store(key = KeyType, ..))]` attribute");
+        };
+        ($_Item:ident; [custom(map_store(key))] $($_rest:tt)*) => {};
+        ($_Item:ident; [custom(map_store(self_key))] $($_rest:tt)*) => {};
+        ($_Item:ident; [custom(map_store(key, $($_key:tt)+))] $($_rest:tt)*) => {};
+        ($Item:ident; [$($_other_meta:tt)+] $($rest:tt)*) => {
+            $crate::map_store_key_attr!($Item; $($rest)*);
+        };
+    }
+
+    /// whether `custom(map_store(escape_keys))` is present
+    #[macro_export]
+    macro_rules! map_store_escape_keys {
+        () => {
+            false
+        };
+        ([custom(map_store(escape_keys))] $($_rest:tt)*) => {
+            true
+        };
+        ([$($_other_meta:tt)+] $($rest:tt)*) => {
+            $crate::map_store_escape_keys!($($rest)*)
+        };
+    }
+
+    /// the checked `custom(map_store(separator = "..."))` if present, otherwise `':'`, only
+    /// expands inside a `map::Prefix` impl
+    #[macro_export]
+    macro_rules! map_store_separator {
+        () => {
+            ':'
+        };
+        ([custom(map_store(separator = $separator:literal))] $($_rest:tt)*) => {
+            $crate::map::checked_separator($separator, Self::KEY_PREFIX, Self::ESCAPE_KEYS)
+        };
+        ([$($_other_meta:tt)+] $($rest:tt)*) => {
+            $crate::map_store_separator!($($rest)*)
+        };
+    }
+
+    /// whether `custom(map_store(lowercase_keys))` is present
+    #[macro_export]
+    macro_rules! map_store_lowercase_keys {
+        () => {
+            false
+        };
+        ([custom(map_store(lowercase_keys))] $($_rest:tt)*) => {
+            true
+        };
+        ([$($_other_meta:tt)+] $($rest:tt)*) => {
+            $crate::map_store_lowercase_keys!($($rest)*)
+        };
+    }
+
+    /// `KEY_PREFIX` from `custom(map_store(prefix = "..."))` if present, otherwise `$default`
+    #[macro_export]
+    macro_rules! map_store_key_prefix {
+        ($default:expr;) => {
+            $default
+        };
+        ($default:expr; [custom(map_store(prefix = $prefix:literal))] $($_rest:tt)*) => {{
+            assert!(!$prefix.is_empty(), "`map_store(prefix = ...)` must not be empty");
+            $prefix
+        }};
+        ($default:expr; [$($_other_meta:tt)+] $($rest:tt)*) => {
+            $crate::map_store_key_prefix!($default; $($rest)*)
+        };
+    }
+
+    /// splits the combined `custom(map_store(key = KeyType, flag, name = "..", ..))` form into one
+    /// attribute per behaviour, then expands `MapStoreImpl!`
+    #[macro_export]
+    macro_rules! map_store_normalize_attrs {
+        (
+            [$($done:tt)*]
+            #[custom(map_store(
+                key = $key:ty $(, $attr:ident $(= $value:literal)? $(($($args:tt)*))?)* $(,)?
+            ))]
+            $($rest:tt)*
+        ) => {
+            $crate::map_store_normalize_attrs!(
+                [
+                    $($done)*
+                    #[custom(map_store(key, $key))]
+                    $(#[custom(map_store($attr $(= $value)? $(($($args)*))?))])*
+                ]
+                $($rest)*
+            );
+        };
+        ([$($done:tt)*] #[$($meta_item:tt)+] $($rest:tt)*) => {
+            $crate::map_store_normalize_attrs!([$($done)* #[$($meta_item)+]] $($rest)*);
+        };
+        ([$($done:tt)*] $($item:tt)+) => {
+            $crate::MapStoreImpl! { @normalized $($done)* $($item)+ }
+        };
+    }
+
+    /// stores the newtype under `map::Prefix::KEY_PREFIX` followed by the encoded map key, requires
+    /// `custom(map_store(key = KeyType))` or `custom(map_store(self_key))`
+    ///
+    /// ```
+    /// use macro_rules_attribute::derive;
+    /// use newtype_macros::prelude::*;
+    /// use newtype_macros::{MapStoreImpl, UintNewtypeImpl};
+    ///
+    /// #[derive(Debug, PartialEq, UintNewtypeImpl!, MapStoreImpl!)]
+    /// #[custom(map_store(key = String, prefix = "balances"))]
+    /// struct Balance(u128);
+    ///
+    /// let mut storage = MemoryStorage::default();
+    ///
+    /// Balance(5).save_at(&mut storage, "alice".to_owned());
+    ///
+    /// assert_eq!(Balance::load_at(&storage, "alice".to_owned()), Some(Balance(5)));
+    /// assert_eq!(Balance::load_at(&storage, "bob".to_owned()), None);
+    /// ```
+    #[macro_export]
+    macro_rules! MapStoreImpl {
+        (@store $Item:ident $([$($meta_item:tt)+])*) => {
+            const _: fn() = || {
+                fn byte_serde<T: $crate::ByteSerde>() {}
+                byte_serde::<$Item>();
+            };
+
+            $crate::map_store_key_attr!($Item; $([$($meta_item)+])*);
+
+            $crate::map_store_exclusive_attrs!($([$($meta_item)+])*);
+
+            const _: char = <$Item as $crate::map::Prefix>::SEPARATOR;
+
+            $(
+                $crate::store_map_derive_attrs!($Item, $($meta_item)+);
+            )*
+        };
+        (
+        @normalized
+        $(#[$($meta_item:tt)+])*
+        $pub:vis struct $Item:ident($_field_vis:vis $Inner:ident $(<$($_InnerParam:ty),+>)?);
+    ) => {
+            impl $crate::map::Prefix for $Item {
+                $crate::paste! {
+                    const KEY_PREFIX: &'static str = $crate::map_store_key_prefix!(
+                        concat!(module_path!(), "::", stringify!([< $Item:snake _ $Inner:snake >]));
+                        $([$($meta_item)+])*
+                    );
+                }
+
+                const ESCAPE_KEYS: bool = $crate::map_store_escape_keys!($([$($meta_item)+])*);
+
+                const LOWERCASE_KEYS: bool = $crate::map_store_lowercase_keys!($([$($meta_item)+])*);
+
+                const SEPARATOR: char = $crate::map_store_separator!($([$($meta_item)+])*);
+
+                $crate::map_store_index_entry!($([$($meta_item)+])*);
+            }
+
+            $crate::MapStoreImpl!(@store $Item $([$($meta_item)+])*);
+        };
+        (
+        @normalized
+        $(#[$($meta_item:tt)+])*
+        $pub:vis struct $Item:ident($_field_vis:vis [$Elem:ident; $_len:expr]);
+    ) => {
+            impl $crate::map::Prefix for $Item {
+                $crate::paste! {
+                    const KEY_PREFIX: &'static str = $crate::map_store_key_prefix!(
+                        concat!(module_path!(), "::", stringify!([< $Item:snake _ $Elem:snake _array >]));
+                        $([$($meta_item)+])*
+                    );
+                }
+
+                const ESCAPE_KEYS: bool = $crate::map_store_escape_keys!($([$($meta_item)+])*);
+
+                const LOWERCASE_KEYS: bool = $crate::map_store_lowercase_keys!($([$($meta_item)+])*);
+
+                const SEPARATOR: char = $crate::map_store_separator!($([$($meta_item)+])*);
+
+                $crate::map_store_index_entry!($([$($meta_item)+])*);
+            }
+
+            $crate::MapStoreImpl!(@store $Item $([$($meta_item)+])*);
+        };
+        (
+        @normalized
+        $(#[$($meta_item:tt)+])*
+        $pub:vis enum $Item:ident { $($_body:tt)* }
+    ) => {
+            impl $crate::map::Prefix for $Item {
+                $crate::paste! {
+                    const KEY_PREFIX: &'static str = $crate::map_store_key_prefix!(
+                        concat!(module_path!(), "::", stringify!([< $Item:snake >]));
+                        $([$($meta_item)+])*
+                    );
+                }
+
+                const ESCAPE_KEYS: bool = $crate::map_store_escape_keys!($([$($meta_item)+])*);
+
+                const LOWERCASE_KEYS: bool = $crate::map_store_lowercase_keys!($([$($meta_item)+])*);
+
+                const SEPARATOR: char = $crate::map_store_separator!($([$($meta_item)+])*);
+
+                $crate::map_store_index_entry!($([$($meta_item)+])*);
+            }
+
+            $crate::MapStoreImpl!(@store $Item $([$($meta_item)+])*);
+        };
+        ($($item:tt)*) => {
+            $crate::map_store_normalize_attrs!([] $($item)*);
+        };
+    }
+}
+
+pub mod non_zero {
+    pub trait Newtype: Sized {
+        type PrimitiveInner;
+        type NonZeroInner: TryFrom<Self::PrimitiveInner>;
+
+        fn non_zero(&self) -> Self::NonZeroInner;
+
+        fn get(&self) -> Self::PrimitiveInner;
+
+        fn from_non_zero_inner(non_zero: Self::NonZeroInner) -> Self;
+
+        /// `from_non_zero_inner`, named like the other kinds' `from_inner`
+        #[must_use]
+        fn from_inner(inner: Self::NonZeroInner) -> Self {
+            Self::from_non_zero_inner(inner)
+        }
+
+        /// applies `f` to the primitive value, `None` if it returns zero
+        fn map_inner<F>(self, f: F) -> Option<Self>
+        where
+            F: FnOnce(Self::PrimitiveInner) -> Self::PrimitiveInner,
+        {
+            Self::NonZeroInner::try_from(f(self.get()))
+                .ok()
+                .map(Self::from_non_zero_inner)
+        }
+
+        /// the uint newtype of the same width, e.g. for arithmetic that may reach zero
+        fn to_uint<U>(&self) -> U
+        where
+            U: crate::uint::Newtype<NonZeroInner = Self::NonZeroInner>,
+        {
+            U::from_non_zero_inner(self.non_zero())
+        }
+    }
+
+    pub trait FromNonZero: Sized + Newtype {
+        #[must_use]
+        fn from_non_zero<NonZero>(non_zero: NonZero) -> Self
+        where
+            Self::NonZeroInner: From<NonZero>;
+    }
+
+    /// `from_inner` checked by the `Validate` impl, from `custom(non_zero_newtype(validate))`
+    pub trait TryNew: Sized + Newtype {
+        fn try_new(non_zero: Self::NonZeroInner) -> Result<Self, crate::ValidationError>;
+    }
+
+    /// `None` for zero, or a value rejected by `custom(non_zero_newtype(validate))`
+    pub trait CheckedNew: Sized + Newtype {
+        #[must_use]
+        fn checked_new<T>(t: T) -> Option<Self>
+        where
+            Self::PrimitiveInner: From<T>;
+    }
+
+    /// no `saturating_sub`, there is no non-zero value to saturate to,
+    /// unsigned only, signed sums can reach zero,
+    /// the checked ops return `None` on a result rejected by `custom(non_zero_newtype(validate))`
+    pub trait Arithmetic: Sized + Newtype {
+        fn checked_add(self, rhs: Self) -> Option<Self>;
+
+        fn checked_sub(self, rhs: Self) -> Option<Self>;
+
+        fn saturating_add(self, rhs: Self) -> Self;
+    }
+
+    #[macro_export]
+    macro_rules! non_zero_newtype_derive_attrs {
+        ($Item:ident, custom(non_zero_newtype(validate))) => {
+            impl $crate::non_zero::TryNew for $Item {
+                fn try_new(non_zero: Self::NonZeroInner) -> Result<Self, $crate::ValidationError> {
+                    let value = Self(non_zero);
+
+                    value.check_valid()?;
+
+                    Ok(value)
+                }
+            }
+        };
+        ($Item:ident, custom(non_zero_newtype(from_non_zero))) => {
+            impl $crate::non_zero::FromNonZero for $Item {
+                fn from_non_zero<NonZero>(non_zero: NonZero) -> Self
+                where
+                    Self::NonZeroInner: From<NonZero>,
+                {
+                    Self(Self::NonZeroInner::from(non_zero)).assert_valid()
+                }
+            }
+        };
+        ($Item:ident, custom(non_zero_newtype(uint_sibling = $Sibling:ty))) => {
+            impl From<$Item> for $Sibling {
+                fn from(non_zero: $Item) -> Self {
+                    $crate::non_zero::Newtype::to_uint(&non_zero)
+                }
+            }
+
+            impl TryFrom<$Sibling> for $Item {
+                type Error = $Sibling;
+
+                fn try_from(uint: $Sibling) -> Result<Self, Self::Error> {
+                    $crate::uint::Newtype::try_into_non_zero(&uint).ok_or(uint)
+                }
+            }
+        };
+        ($Item:ident, custom(non_zero_newtype(checked_new))) => {
+            impl $crate::non_zero::CheckedNew for $Item {
+                fn checked_new<T>(t: T) -> Option<Self>
+                where
+                    Self::PrimitiveInner: From<T>,
+                {
+                    Self::NonZeroInner::new(Self::PrimitiveInner::from(t))
+                        .map(Self)
+                        .filter(|value| value.check_valid().is_ok())
+                }
+            }
+        };
+        ($Item:ident, custom(non_zero_newtype(arithmetic))) => {
+            impl $crate::non_zero::Arithmetic for $Item {
+                fn checked_add(self, rhs: Self) -> Option<Self> {
+                    self.0
+                        .get()
+                        .checked_add(rhs.0.get())
+                        .and_then(Self::NonZeroInner::new)
+                        .map(Self)
+                        .filter(|value| value.check_valid().is_ok())
+                }
+
+                fn checked_sub(self, rhs: Self) -> Option<Self> {
+                    self.0
+                        .get()
+                        .checked_sub(rhs.0.get())
+                        .and_then(Self::NonZeroInner::new)
+                        .map(Self)
+                        .filter(|value| value.check_valid().is_ok())
+                }
+
+                fn saturating_add(self, rhs: Self) -> Self {
+                    Self(self.0.saturating_add(rhs.0.get())).assert_valid()
+                }
+            }
+        };
+        ($Item:ident, custom(non_zero_newtype(eq))) => {
+            impl PartialEq for $Item {
+                fn eq(&self, other: &Self) -> bool {
+                    self.0 == other.0
+                }
+            }
+
+            impl Eq for $Item {}
+        };
+        ($Item:ident, custom(non_zero_newtype(ord))) => {
+            impl PartialOrd for $Item {
+                fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
+                    Some(self.cmp(other))
+                }
+            }
+
+            impl Ord for $Item {
+                fn cmp(&self, other: &Self) -> std::cmp::Ordering {
+                    self.0.cmp(&other.0)
+                }
+            }
+        };
+        ($Item:ident, custom(non_zero_newtype(hash))) => {
+            impl std::hash::Hash for $Item {
+                fn hash<H: std::hash::Hasher>(&self, state: &mut H) {
+                    self.0.hash(state)
+                }
+            }
+        };
+        ($Item:ident, custom(non_zero_newtype(copy))) => {
+            impl Clone for $Item {
+                fn clone(&self) -> Self {
+                    *self
+                }
+            }
+
+            impl Copy for $Item {}
+        };
+        ($Item:ident, custom(non_zero_newtype(display))) => {
+            impl std::fmt::Display for $Item {
+                fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+                    std::fmt::Display::fmt(&self.0, f)
+                }
+            }
+        };
+        // `full` expands to `from_non_zero`, `checked_new`, `eq`, `ord`, `hash`, `copy` & `display`,
+        // not `arithmetic`, which is unsigned only
+        ($Item:ident, custom(non_zero_newtype(full))) => {
+            $crate::non_zero_newtype_derive_attrs!($Item, custom(non_zero_newtype(from_non_zero)));
+            $crate::non_zero_newtype_derive_attrs!($Item, custom(non_zero_newtype(checked_new)));
+            $crate::non_zero_newtype_derive_attrs!($Item, custom(non_zero_newtype(eq)));
+            $crate::non_zero_newtype_derive_attrs!($Item, custom(non_zero_newtype(ord)));
+            $crate::non_zero_newtype_derive_attrs!($Item, custom(non_zero_newtype(hash)));
+            $crate::non_zero_newtype_derive_attrs!($Item, custom(non_zero_newtype(copy)));
+            $crate::non_zero_newtype_derive_attrs!($Item, custom(non_zero_newtype(display)));
+        };
+        ($_Item:ident, custom(non_zero_newtype(default))) => {
+            compile_error!("non_zero newtypes have no zero `Default`, use `custom(item_store(always))` to load a value that is always present");
+        };
+        ($_Item:ident, custom(item_store(default))) => {
+            compile_error!("non_zero newtypes have no zero `Default`, use `custom(item_store(always))` to load a value that is always present");
+        };
+        ($Item:ident, custom(non_zero_newtype(transparent_debug))) => {
+            impl std::fmt::Debug for $Item {
+                fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+                    std::fmt::Debug::fmt(&self.0, f)
+                }
+            }
+        };
+        ($_Item:ident, custom(non_zero_newtype(versioned $(= $_version:literal)?))) => {};
+        ($_Item:ident, custom(non_zero_newtype(endian = "be"))) => {};
+        ($_Item:ident, custom(non_zero_newtype(endian = "le"))) => {};
+        ($_Item:ident, custom(non_zero_newtype(endian = $_endian:literal))) => {
+            compile_error!("`custom(non_zero_newtype(endian = ...))` is either \"be\" or \"le\"");
+        };
+        ($_Item:ident, custom(non_zero_newtype($($unknown:tt)*))) => {
+            compile_error!(concat!("unknown `non_zero_newtype` attribute: `", stringify!($($unknown)*), "`"));
+        };
+        ($_Item:ident, $($_other_meta:tt)+) => {};
+    }
+
+    /// no `Default`, there is no non-zero value to default to, see `custom(item_store(always))`
+    ///
+    /// ```
+    /// use std::num::NonZeroU32;
+    ///
+    /// use macro_rules_attribute::derive;
+    /// use newtype_macros::prelude::*;
+    /// use newtype_macros::{ItemStoreImpl, NonZeroNewtypeImpl};
+    ///
+    /// #[derive(Debug, PartialEq, NonZeroNewtypeImpl!, ItemStoreImpl!)]
+    /// #[custom(non_zero_newtype(checked_new))]
+    /// struct Epoch(NonZeroU32);
+    ///
+    /// let mut storage = MemoryStorage::default();
+    ///
+    /// assert_eq!(Epoch::checked_new(0u32), None);
+    ///
+    /// Epoch::checked_new(3u32).unwrap().save(&mut storage);
+    ///
+    /// assert_eq!(Epoch::load(&storage).map(|epoch| epoch.get()), Some(3));
+    /// ```
+    #[macro_export]
+    macro_rules! NonZeroNewtypeImpl {
+        (
+        $(#[$($meta_item:tt)+])*
+        $pub:vis struct $Newtype:ident($_field_vis:vis $NonZeroInteger:path);
+    ) => {
+            $crate::assert_transparent_size!($Newtype, $NonZeroInteger);
+
+            const _: fn() = || {
+                fn non_zero_inner<T: $crate::Primitive>() {}
+                non_zero_inner::<$NonZeroInteger>();
+            };
+
+            impl $Newtype {
+                const VERSION: Option<u8> = $crate::newtype_version!($([$($meta_item)+])*);
+                /// stored length of the value, before any version byte
+                pub const BYTE_LEN: usize = std::mem::size_of::<<<Self as $crate::non_zero::Newtype>::PrimitiveInner as $crate::FixedWidthBytes>::Bytes>();
+                const LITTLE_ENDIAN: bool = $crate::newtype_little_endian!($([$($meta_item)+])*);
+
+                /// `Validate::validate` if `custom(non_zero_newtype(validate))` is present, otherwise `Ok(())`
+                fn check_valid(&self) -> Result<(), $crate::ValidationError> {
+                    $crate::newtype_validate!(self; $([$($meta_item)+])*)
+                }
+
+                /// `self` for the infallible constructors, panics if `check_valid` rejects it
+                fn assert_valid(self) -> Self {
+                    if let Err(err) = self.check_valid() {
+                        panic!("{err}");
+                    }
+
+                    self
+                }
+
+                fn from_owned_bytes(bytes: Vec<u8>) -> Self {
+                    Self::try_from_owned_bytes(bytes).expect("stored valid value")
+                }
+
+                fn try_from_owned_bytes(bytes: Vec<u8>) -> Result<Self, $crate::LoadError> {
+                    Self::try_from_borrowed_bytes(&bytes)
+                }
+
+                fn from_borrowed_bytes(bytes: &[u8]) -> Self {
+                    Self::try_from_borrowed_bytes(bytes).expect("stored valid value")
+                }
+
+                fn try_from_borrowed_bytes(bytes: &[u8]) -> Result<Self, $crate::LoadError> {
+                    let bytes = $crate::version::untag_slice(Self::VERSION, bytes)?;
+
+                    let mut fixed_bytes: <<Self as $crate::non_zero::Newtype>::PrimitiveInner as $crate::FixedWidthBytes>::Bytes = TryFrom::try_from(bytes).map_err(|_| {
+                        $crate::LoadError::ByteLength {
+                            expected: Self::BYTE_LEN,
+                            found: bytes.len(),
+                        }
+                    })?;
+
+                    let primative = if Self::LITTLE_ENDIAN {
+                        fixed_bytes.reverse();
+                        $crate::FixedWidthBytes::from_fixed_width_bytes(fixed_bytes)
+                    } else {
+                        $crate::OrderedBytes::from_ordered_bytes(fixed_bytes)
+                    }
+                    .ok_or($crate::LoadError::Overflow)?;
+
+                    let value = <Self as $crate::non_zero::Newtype>::NonZeroInner::new(primative)
+                        .map(Self)
+                        .ok_or($crate::LoadError::Zero)?;
+
+                    value.check_valid()?;
+
+                    Ok(value)
+                }
+
+                fn to_owned_bytes(&self) -> Vec<u8> {
+                    let bytes = if Self::LITTLE_ENDIAN {
+                        let mut bytes = $crate::FixedWidthBytes::to_fixed_width_bytes(self.0.get());
+                        bytes.reverse();
+                        bytes
+                    } else {
+                        $crate::OrderedBytes::to_ordered_bytes(self.0.get())
+                    }
+                    .to_vec();
+
+                    $crate::version::tag(Self::VERSION, bytes)
+                }
+
+                fn to_owned_bytes_framed(&self) -> Vec<u8> {
+                    $crate::frame::frame(self.to_owned_bytes())
+                }
+
+                fn from_owned_bytes_framed(bytes: &[u8]) -> Result<(Self, &[u8]), $crate::LoadError> {
+                    let (value, rest) = $crate::frame::unframe(bytes)?;
+
+                    Ok((Self::try_from_owned_bytes(value)?, rest))
+                }
+            }
+
+            impl $crate::non_zero::Newtype for $Newtype {
+                type NonZeroInner = $NonZeroInteger;
+                type PrimitiveInner = <Self::NonZeroInner as $crate::Primitive>::Primative;
+
+                fn non_zero(&self) -> Self::NonZeroInner {
+                    self.0
+                }
+
+                fn get(&self) -> Self::PrimitiveInner {
+                    self.0.get()
+                }
+
+                fn from_non_zero_inner(non_zero: Self::NonZeroInner) -> Self {
+                    Self(non_zero).assert_valid()
+                }
+            }
+
+            $crate::impl_byte_serde!($Newtype, fixed_len = Self::BYTE_LEN + Self::VERSION.is_some() as usize);
+
+            $crate::impl_serde!($Newtype, $NonZeroInteger);
+
+            impl From<$Newtype> for $NonZeroInteger {
+                fn from(newtype: $Newtype) -> Self {
+                    newtype.0
+                }
+            }
+
+            $(
+                $crate::non_zero_newtype_derive_attrs!($Newtype, $($meta_item)+);
+            )*
+        };
+    }
+}
+
+pub mod uint {
+    pub trait Newtype: Sized {
+        type PrimitiveInner;
+        type NonZeroInner;
+
+        fn get(&self) -> Self::PrimitiveInner;
+
+        fn non_zero(&self) -> Option<Self::NonZeroInner>;
+
+        fn from_non_zero_inner(non_zero: Self::NonZeroInner) -> Self;
+
+        #[must_use]
+        fn from_inner(inner: Self::PrimitiveInner) -> Self;
+
+        /// `from_inner`, returning the `Validate` error instead of panicking
+        fn try_from_inner(inner: Self::PrimitiveInner) -> Result<Self, crate::ValidationError>;
+
+        /// applies `f` to the primitive value
+        #[must_use]
+        fn map_inner<F>(self, f: F) -> Self
+        where
+            F: FnOnce(Self::PrimitiveInner) -> Self::PrimitiveInner,
+        {
+            Self::from_inner(f(self.get()))
+        }
+
+        /// the non_zero newtype of the same width, `None` for zero
+        fn try_into_non_zero<N>(&self) -> Option<N>
+        where
+            N: crate::non_zero::Newtype<NonZeroInner = Self::NonZeroInner>,
+        {
+            self.non_zero().map(N::from_non_zero_inner)
+        }
+
+        /// the same value in a newtype of another width, `None` if it doesn't fit
+        /// or the other newtype's `Validate` impl rejects it
+        fn try_narrow<N>(&self) -> Option<N>
+        where
+            N: Newtype,
+            N::PrimitiveInner: TryFrom<Self::PrimitiveInner>,
+        {
+            N::PrimitiveInner::try_from(self.get())
+                .ok()
+                .and_then(|inner| N::try_from_inner(inner).ok())
+        }
+
+        /// `try_narrow`, clamped to the bounds of the other width, `None` if the
+        /// other newtype's `Validate` impl rejects the clamped value
+        fn saturating_narrow<N>(&self) -> Option<N>
+        where
+            N: Newtype,
+            N::PrimitiveInner: TryFrom<Self::PrimitiveInner> + crate::IntBounds,
+            Self::PrimitiveInner: PartialOrd + Default,
+        {
+            let inner = self.get();
+
+            let saturated = if inner > Self::PrimitiveInner::default() {
+                <N::PrimitiveInner as crate::IntBounds>::MAX
+            } else {
+                <N::PrimitiveInner as crate::IntBounds>::MIN
+            };
+
+            N::try_from_inner(N::PrimitiveInner::try_from(inner).unwrap_or(saturated)).ok()
+        }
+    }
+
+    /// panics on a value rejected by `custom(uint_newtype(validate))`, see `TryNew`
+    pub trait New: Sized + Newtype {
+        #[must_use]
+        fn new<T>(t: T) -> Self
+        where
+            Self::PrimitiveInner: From<T>;
+    }
+
+    /// `new` checked by the `Validate` impl, from `custom(uint_newtype(validate))`
+    pub trait TryNew: Sized + Newtype {
+        fn try_new<T>(t: T) -> Result<Self, crate::ValidationError>
+        where
+            Self::PrimitiveInner: From<T>;
+    }
+
+    /// the checked ops return `None` on a result rejected by `custom(uint_newtype(validate))`, the
+    /// saturating ops panic on it
+    pub trait Arithmetic: Sized + Newtype {
+        fn checked_add(self, rhs: Self) -> Option<Self>;
+
+        fn checked_sub(self, rhs: Self) -> Option<Self>;
+
+        fn saturating_add(self, rhs: Self) -> Self;
+
+        fn saturating_sub(self, rhs: Self) -> Self;
+    }
+
+    /// `PartialEq` both ways with the inner integer if `custom(uint_newtype(eq_inner))` is present,
+    /// outside of `uint_newtype_derive_attrs!` as the impls need the inner type
+    #[macro_export]
+    macro_rules! uint_newtype_eq_inner {
+        ($_Newtype:ident, $_Uint:ty;) => {};
+        ($Newtype:ident, $Uint:ty; [custom(uint_newtype(eq_inner))] $($_rest:tt)*) => {
+            impl PartialEq<$Uint> for $Newtype {
+                fn eq(&self, other: &$Uint) -> bool {
+                    self.0 == *other
+                }
+            }
+
+            impl PartialEq<$Newtype> for $Uint {
+                fn eq(&self, other: &$Newtype) -> bool {
+                    *self == other.0
+                }
+            }
+        };
+        ($Newtype:ident, $Uint:ty; [$($_other_meta:tt)+] $($rest:tt)*) => {
+            $crate::uint_newtype_eq_inner!($Newtype, $Uint; $($rest)*);
+        };
+    }
+
+    #[macro_export]
+    macro_rules! uint_newtype_derive_attrs {
+        ($Item:ident, custom(uint_newtype(validate))) => {
+            impl $crate::uint::TryNew for $Item {
+                fn try_new<T>(t: T) -> Result<Self, $crate::ValidationError>
+                where
+                    Self::PrimitiveInner: From<T>,
+                {
+                    let value = Self(Self::PrimitiveInner::from(t));
+
+                    value.check_valid()?;
+
+                    Ok(value)
+                }
+            }
+        };
+        ($Item:ident, custom(uint_newtype(new))) => {
+            impl $crate::uint::New for $Item {
+                fn new<T>(t: T) -> Self
+                where
+                    Self::PrimitiveInner: From<T>,
+                {
+                    Self(Self::PrimitiveInner::from(t)).assert_valid()
+                }
+            }
+        };
+        ($Item:ident, custom(uint_newtype(arithmetic))) => {
+            impl $crate::uint::Arithmetic for $Item {
+                fn checked_add(self, rhs: Self) -> Option<Self> {
+                    self.0
+                        .checked_add(rhs.0)
+                        .map(Self)
+                        .filter(|value| value.check_valid().is_ok())
+                }
+
+                fn checked_sub(self, rhs: Self) -> Option<Self> {
+                    self.0
+                        .checked_sub(rhs.0)
+                        .map(Self)
+                        .filter(|value| value.check_valid().is_ok())
+                }
+
+                fn saturating_add(self, rhs: Self) -> Self {
+                    Self(self.0.saturating_add(rhs.0)).assert_valid()
+                }
+
+                fn saturating_sub(self, rhs: Self) -> Self {
+                    Self(self.0.saturating_sub(rhs.0)).assert_valid()
+                }
+            }
+        };
+        ($Item:ident, custom(uint_newtype(eq))) => {
+            impl PartialEq for $Item {
+                fn eq(&self, other: &Self) -> bool {
+                    self.0 == other.0
+                }
+            }
+
+            impl Eq for $Item {}
+        };
+        ($Item:ident, custom(uint_newtype(ord))) => {
+            impl PartialOrd for $Item {
+                fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
+                    Some(self.cmp(other))
+                }
+            }
+
+            impl Ord for $Item {
+                fn cmp(&self, other: &Self) -> std::cmp::Ordering {
+                    self.0.cmp(&other.0)
+                }
+            }
+        };
+        ($Item:ident, custom(uint_newtype(hash))) => {
+            impl std::hash::Hash for $Item {
+                fn hash<H: std::hash::Hasher>(&self, state: &mut H) {
+                    self.0.hash(state)
+                }
+            }
+        };
+        ($Item:ident, custom(uint_newtype(copy))) => {
+            impl Clone for $Item {
+                fn clone(&self) -> Self {
+                    *self
+                }
+            }
+
+            impl Copy for $Item {}
+        };
+        ($Item:ident, custom(uint_newtype(display))) => {
+            impl std::fmt::Display for $Item {
+                fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+                    std::fmt::Display::fmt(&self.0, f)
+                }
+            }
+        };
+        // `full` expands to `new`, `arithmetic`, `eq`, `ord`, `hash`, `copy` & `display`
+        ($Item:ident, custom(uint_newtype(full))) => {
+            $crate::uint_newtype_derive_attrs!($Item, custom(uint_newtype(new)));
+            $crate::uint_newtype_derive_attrs!($Item, custom(uint_newtype(arithmetic)));
+            $crate::uint_newtype_derive_attrs!($Item, custom(uint_newtype(eq)));
+            $crate::uint_newtype_derive_attrs!($Item, custom(uint_newtype(ord)));
+            $crate::uint_newtype_derive_attrs!($Item, custom(uint_newtype(hash)));
+            $crate::uint_newtype_derive_attrs!($Item, custom(uint_newtype(copy)));
+            $crate::uint_newtype_derive_attrs!($Item, custom(uint_newtype(display)));
+        };
+        ($_Item:ident, custom(uint_newtype(eq_inner))) => {};
+        ($Item:ident, custom(uint_newtype(transparent_debug))) => {
+            impl std::fmt::Debug for $Item {
+                fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+                    std::fmt::Debug::fmt(&self.0, f)
+                }
+            }
+        };
+        ($_Item:ident, custom(uint_newtype(versioned $(= $_version:literal)?))) => {};
+        ($_Item:ident, custom(uint_newtype(endian = "be"))) => {};
+        ($_Item:ident, custom(uint_newtype(endian = "le"))) => {};
+        ($_Item:ident, custom(uint_newtype(endian = $_endian:literal))) => {
+            compile_error!("`custom(uint_newtype(endian = ...))` is either \"be\" or \"le\"");
+        };
+        ($_Item:ident, custom(uint_newtype($($unknown:tt)*))) => {
+            compile_error!(concat!("unknown `uint_newtype` attribute: `", stringify!($($unknown)*), "`"));
+        };
+        ($_Item:ident, $($_other_meta:tt)+) => {};
+    }
+
+    /// signed inners are stored with their sign bit flipped like `NonZeroNewtypeImpl!`, so the
+    /// big-endian bytes sort like the values
+    ///
+    /// ```
+    /// use macro_rules_attribute::derive;
+    /// use newtype_macros::prelude::*;
+    /// use newtype_macros::{ItemStoreImpl, UintNewtypeImpl};
+    ///
+    /// #[derive(Debug, PartialEq, UintNewtypeImpl!, ItemStoreImpl!)]
+    /// #[custom(uint_newtype(new))]
+    /// #[custom(uint_newtype(arithmetic))]
+    /// struct Supply(u128);
+    ///
+    /// let mut storage = MemoryStorage::default();
+    ///
+    /// Supply::new(5u8).checked_add(Supply(2)).unwrap().save(&mut storage);
+    ///
+    /// assert_eq!(Supply::load(&storage), Some(Supply(7)));
+    /// ```
+    #[macro_export]
+    macro_rules! UintNewtypeImpl {
+        (
+        $(#[$($meta_item:tt)+])*
+        $pub:vis struct $Newtype:ident($_field_vis:vis $Uint:ty);
+    ) => {
+            $crate::assert_transparent_size!($Newtype, $Uint);
+
+            impl $Newtype {
+                const VERSION: Option<u8> = $crate::newtype_version!($([$($meta_item)+])*);
+                /// stored length of the value, before any version byte
+                pub const BYTE_LEN: usize = std::mem::size_of::<<<Self as $crate::uint::Newtype>::PrimitiveInner as $crate::FixedWidthBytes>::Bytes>();
+                const LITTLE_ENDIAN: bool = $crate::newtype_little_endian!($([$($meta_item)+])*);
+
+                /// `Validate::validate` if `custom(uint_newtype(validate))` is present, otherwise `Ok(())`
+                fn check_valid(&self) -> Result<(), $crate::ValidationError> {
+                    $crate::newtype_validate!(self; $([$($meta_item)+])*)
+                }
+
+                /// `self` for the infallible constructors, panics if `check_valid` rejects it
+                fn assert_valid(self) -> Self {
+                    if let Err(err) = self.check_valid() {
+                        panic!("{err}");
+                    }
+
+                    self
+                }
+
+                fn from_owned_bytes(bytes: Vec<u8>) -> Self {
+                    Self::try_from_owned_bytes(bytes).expect("stored valid value")
+                }
+
+                fn try_from_owned_bytes(bytes: Vec<u8>) -> Result<Self, $crate::LoadError> {
+                    Self::try_from_borrowed_bytes(&bytes)
+                }
+
+                fn from_borrowed_bytes(bytes: &[u8]) -> Self {
+                    Self::try_from_borrowed_bytes(bytes).expect("stored valid value")
+                }
+
+                fn try_from_borrowed_bytes(bytes: &[u8]) -> Result<Self, $crate::LoadError> {
+                    let bytes = $crate::version::untag_slice(Self::VERSION, bytes)?;
+
+                    let mut fixed_bytes: <<Self as $crate::uint::Newtype>::PrimitiveInner as $crate::FixedWidthBytes>::Bytes = TryFrom::try_from(bytes).map_err(|_| {
+                        $crate::LoadError::ByteLength {
+                            expected: Self::BYTE_LEN,
+                            found: bytes.len(),
+                        }
+                    })?;
+
+                    let primative = if Self::LITTLE_ENDIAN {
+                        fixed_bytes.reverse();
+                        $crate::FixedWidthBytes::from_fixed_width_bytes(fixed_bytes)
+                    } else {
+                        $crate::OrderedBytes::from_ordered_bytes(fixed_bytes)
+                    }
+                    .ok_or($crate::LoadError::Overflow)?;
+
+                    let value = Self(primative);
+
+                    value.check_valid()?;
+
+                    Ok(value)
+                }
+
+                fn to_owned_bytes(&self) -> Vec<u8> {
+                    let bytes = if Self::LITTLE_ENDIAN {
+                        let mut bytes = $crate::FixedWidthBytes::to_fixed_width_bytes(self.0);
+                        bytes.reverse();
+                        bytes
+                    } else {
+                        $crate::OrderedBytes::to_ordered_bytes(self.0)
+                    }
+                    .to_vec();
+
+                    $crate::version::tag(Self::VERSION, bytes)
+                }
+
+                fn to_owned_bytes_framed(&self) -> Vec<u8> {
+                    $crate::frame::frame(self.to_owned_bytes())
+                }
+
+                fn from_owned_bytes_framed(bytes: &[u8]) -> Result<(Self, &[u8]), $crate::LoadError> {
+                    let (value, rest) = $crate::frame::unframe(bytes)?;
+
+                    Ok((Self::try_from_owned_bytes(value)?, rest))
+                }
+            }
+
+            impl $crate::uint::Newtype for $Newtype {
+                type PrimitiveInner = $Uint;
+                type NonZeroInner = <$Uint as $crate::NonZeroEquivalent>::NonZeroEquivalent;
+
+                fn get(&self) -> Self::PrimitiveInner {
+                    self.0
+                }
+
+                fn non_zero(&self) -> Option<Self::NonZeroInner> {
+                    Self::NonZeroInner::new(self.0)
+                }
+
+                fn from_non_zero_inner(non_zero: Self::NonZeroInner) -> Self {
+                    Self(non_zero.get()).assert_valid()
+                }
+
+                fn from_inner(inner: Self::PrimitiveInner) -> Self {
+                    Self(inner).assert_valid()
+                }
+
+                fn try_from_inner(inner: Self::PrimitiveInner) -> Result<Self, $crate::ValidationError> {
+                    let value = Self(inner);
+
+                    value.check_valid().map(|()| value)
+                }
+            }
+
+            $crate::impl_byte_serde!($Newtype, fixed_len = Self::BYTE_LEN + Self::VERSION.is_some() as usize);
+
+            $crate::impl_serde!($Newtype, $Uint);
+
+            impl From<$Newtype> for $Uint {
+                fn from(newtype: $Newtype) -> Self {
+                    newtype.0
+                }
+            }
+
+            $crate::uint_newtype_eq_inner!($Newtype, $Uint; $([$($meta_item)+])*);
+
+            $(
+                $crate::uint_newtype_derive_attrs!($Newtype, $($meta_item)+);
+            )*
+        };
+    }
+}
+
+pub mod string {
+    use std::collections::HashSet;
+    use std::sync::{Arc, Mutex, OnceLock};
+
+    pub trait Newtype: Sized {
+        /// panics outside of the `custom(string_newtype(len(..)))` bounds or on a value rejected by
+        /// `custom(string_newtype(validate))`, see `TryNew`
+        #[must_use]
+        fn new<S>(s: S) -> Self
+        where
+            S: Into<String>;
+
+        /// `new`, named like the other kinds' `from_inner`
+        #[must_use]
+        fn from_inner(inner: String) -> Self {
+            Self::new(inner)
+        }
+
+        fn as_str(&self) -> &str;
+
+        fn into_string(self) -> String;
+
+        /// number of chars, O(n), see `byte_len` for the O(1) length
+        fn len(&self) -> usize {
+            self.as_str().chars().count()
+        }
+
+        fn byte_len(&self) -> usize {
+            self.as_str().len()
+        }
+
+        fn is_empty(&self) -> bool {
+            self.as_str().is_empty()
+        }
+    }
+
+    pub trait New: Sized {
+        #[must_use]
+        fn new(s: String) -> Self;
+    }
+
+    /// `Newtype::new` returning the rejection instead of panicking
+    pub trait TryNew: Sized {
+        fn try_new<S>(s: S) -> Result<Self, StringError>
+        where
+            S: Into<String>;
+    }
+
+    /// a string rejected by `TryNew`
+    #[derive(Debug, Clone, PartialEq, Eq)]
+    pub enum StringError {
+        Len(StringLenError),
+        Validation(crate::ValidationError),
+    }
+
+    impl From<StringLenError> for StringError {
+        fn from(err: StringLenError) -> Self {
+            Self::Len(err)
+        }
+    }
+
+    impl From<crate::ValidationError> for StringError {
+        fn from(err: crate::ValidationError) -> Self {
+            Self::Validation(err)
+        }
+    }
+
+    impl std::fmt::Display for StringError {
+        fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+            match self {
+                Self::Len(err) => err.fmt(f),
+                Self::Validation(err) => err.fmt(f),
+            }
+        }
+    }
+
+    impl std::error::Error for StringError {}
+
+    /// byte length outside of a `custom(string_newtype(len(min = .., max = ..)))` bound
+    #[derive(Debug, Clone, PartialEq, Eq)]
+    pub struct StringLenError {
+        pub len: usize,
+        pub min: usize,
+        pub max: usize,
+    }
+
+    impl std::fmt::Display for StringLenError {
+        fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+            write!(
+                f,
+                "string byte length {} outside of {}..={}",
+                self.len, self.min, self.max
+            )
+        }
+    }
+
+    impl std::error::Error for StringLenError {}
+
+    pub fn check_len(s: &str, (min, max): (usize, usize)) -> Result<(), StringLenError> {
+        let len = s.len();
+
+        if len < min || len > max {
+            return Err(StringLenError { len, min, max });
+        }
+
+        Ok(())
+    }
+
+    /// `(min, max)` byte length from `custom(string_newtype(len(..)))` if present, otherwise unbounded
+    #[macro_export]
+    macro_rules! string_newtype_len_bounds {
+        () => {
+            (0, usize::MAX)
+        };
+        ([custom(string_newtype(len(min = $min:expr, max = $max:expr)))] $($_rest:tt)*) => {{
+            assert!($min <= $max, "`string_newtype(len(..))` requires min <= max");
+            ($min, $max)
+        }};
+        ([custom(string_newtype(len(min = $min:expr)))] $($_rest:tt)*) => {
+            ($min, usize::MAX)
+        };
+        ([custom(string_newtype(len(max = $max:expr)))] $($_rest:tt)*) => {
+            (0, $max)
+        };
+        ([$($_other_meta:tt)+] $($rest:tt)*) => {
+            $crate::string_newtype_len_bounds!($($rest)*)
+        };
+    }
+
+    /// runs the stored bytes through `compress` / `decompress` if `custom(string_newtype(compress))`
+    /// is present
+    #[cfg(feature = "compress")]
+    #[macro_export]
+    macro_rules! string_newtype_compress {
+        (@$_direction:ident $bytes:ident;) => {
+            $bytes
+        };
+        (@compress $bytes:ident; [custom(string_newtype(compress))] $($_rest:tt)*) => {
+            $crate::compress::compress($bytes)
+        };
+        (@decompress $bytes:ident; [custom(string_newtype(compress))] $($_rest:tt)*) => {
+            $crate::compress::decompress($bytes)?
+        };
+        (@$direction:ident $bytes:ident; [$($_other_meta:tt)+] $($rest:tt)*) => {
+            $crate::string_newtype_compress!(@$direction $bytes; $($rest)*)
+        };
+    }
+
+    /// without the `compress` feature, rejects `custom(string_newtype(compress))`
+    #[cfg(not(feature = "compress"))]
+    #[macro_export]
+    macro_rules! string_newtype_compress {
+        (@$_direction:ident $bytes:ident;) => {
+            $bytes
+        };
+        (@$_direction:ident $_bytes:ident; [custom(string_newtype(compress))] $($_rest:tt)*) => {
+            compile_error!("`custom(string_newtype(compress))` requires the `compress` feature of `newtype-macros`")
+        };
+        (@$direction:ident $bytes:ident; [$($_other_meta:tt)+] $($rest:tt)*) => {
+            $crate::string_newtype_compress!(@$direction $bytes; $($rest)*)
+        };
+    }
+
+    /// whether `custom(string_newtype(lossy))` is present, replacing invalid utf-8 rather than
+    /// failing to load
+    #[macro_export]
+    macro_rules! string_newtype_lossy {
+        () => {
+            false
+        };
+        ([custom(string_newtype(lossy))] $($_rest:tt)*) => {
+            true
+        };
+        ([$($_other_meta:tt)+] $($rest:tt)*) => {
+            $crate::string_newtype_lossy!($($rest)*)
+        };
+    }
+
+    #[derive(Default)]
+    struct Interner {
+        strings: HashSet<Arc<str>>,
+        /// `strings.len()` at which the next insert sweeps
+        sweep_at: usize,
+    }
+
+    /// sweeps never run below this many interned strings
+    const MIN_SWEEP_AT: usize = 64;
+
+    /// returns the process-wide shared allocation for `s`, allocating it on first use
+    ///
+    /// a string stays interned while any value holds it, once only the interner does it's dropped
+    /// by the next sweep, which runs on insert whenever the set has doubled since the last one, so
+    /// memory follows the live values rather than every string ever constructed or loaded
+    pub fn intern(s: String) -> Arc<str> {
+        static INTERNED: OnceLock<Mutex<Interner>> = OnceLock::new();
+
+        let mut interner = INTERNED
+            .get_or_init(Default::default)
+            .lock()
+            .expect("interner lock not poisoned");
+
+        if let Some(shared) = interner.strings.get(s.as_str()) {
+            return shared.clone();
+        }
+
+        if interner.strings.len() >= interner.sweep_at {
+            interner
+                .strings
+                .retain(|shared| Arc::strong_count(shared) > 1);
+            interner.sweep_at = (interner.strings.len() * 2).max(MIN_SWEEP_AT);
+        }
+
+        let shared: Arc<str> = Arc::from(s);
+
+        interner.strings.insert(shared.clone());
+
+        shared
+    }
+
+    #[macro_export]
+    macro_rules! string_newtype_derive_attrs {
+        ($Item:ident, custom(string_newtype(len($($_bounds:tt)+)))) => {};
+        ($_Item:ident, custom(string_newtype(validate))) => {};
+        ($Item:ident, custom(string_newtype(intern))) => {
+            impl $Item {
+                fn intern(s: String) -> std::sync::Arc<str> {
+                    $crate::string::intern(s)
+                }
+            }
+        };
+        ($Item:ident, custom(string_newtype(hash))) => {
+            impl std::hash::Hash for $Item {
+                fn hash<H: std::hash::Hasher>(&self, state: &mut H) {
+                    self.0.hash(state)
+                }
+            }
+        };
+        ($Item:ident, custom(string_newtype(display))) => {
+            impl std::fmt::Display for $Item {
+                fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+                    std::fmt::Display::fmt(&self.0, f)
+                }
+            }
+        };
+        // `full` expands to `hash` & `display`
+        ($Item:ident, custom(string_newtype(full))) => {
+            $crate::string_newtype_derive_attrs!($Item, custom(string_newtype(hash)));
+            $crate::string_newtype_derive_attrs!($Item, custom(string_newtype(display)));
+        };
+        ($Item:ident, custom(string_newtype(eq_inner))) => {
+            impl PartialEq<str> for $Item {
+                fn eq(&self, other: &str) -> bool {
+                    <Self as $crate::string::Newtype>::as_str(self) == other
+                }
+            }
+
+            impl PartialEq<&str> for $Item {
+                fn eq(&self, other: &&str) -> bool {
+                    <Self as $crate::string::Newtype>::as_str(self) == *other
+                }
+            }
+
+            impl PartialEq<$Item> for str {
+                fn eq(&self, other: &$Item) -> bool {
+                    self == <$Item as $crate::string::Newtype>::as_str(other)
+                }
+            }
+
+            impl PartialEq<$Item> for &str {
+                fn eq(&self, other: &$Item) -> bool {
+                    *self == <$Item as $crate::string::Newtype>::as_str(other)
+                }
+            }
+        };
+        ($Item:ident, custom(string_newtype(transparent_debug))) => {
+            impl std::fmt::Debug for $Item {
+                fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+                    std::fmt::Debug::fmt(&self.0, f)
+                }
+            }
+        };
+        ($_Item:ident, custom(string_newtype(versioned $(= $_version:literal)?))) => {};
+        ($_Item:ident, custom(string_newtype(compress))) => {};
+        ($_Item:ident, custom(string_newtype(lossy))) => {};
+        ($_Item:ident, custom(string_newtype($($unknown:tt)*))) => {
+            compile_error!(concat!("unknown `string_newtype` attribute: `", stringify!($($unknown)*), "`"));
+        };
+        ($_Item:ident, $($_other_meta:tt)+) => {};
+    }
+
+    /// for `String` newtypes, or `Arc<str>` newtypes with `custom(string_newtype(intern))`, stored as
+    /// the utf-8 bytes
+    ///
+    /// ```
+    /// use macro_rules_attribute::derive;
+    /// use newtype_macros::prelude::*;
+    /// use newtype_macros::{ItemStoreImpl, StringNewtypeImpl};
+    ///
+    /// #[derive(Debug, PartialEq, StringNewtypeImpl!, ItemStoreImpl!)]
+    /// #[custom(string_newtype(len(min = 3, max = 8)))]
+    /// struct Ticker(String);
+    ///
+    /// let mut storage = MemoryStorage::default();
+    ///
+    /// assert!(Ticker::try_new("ab").is_err());
+    ///
+    /// Ticker::new("atom").save(&mut storage);
+    ///
+    /// assert_eq!(Ticker::load(&storage).unwrap().as_str(), "atom");
+    /// ```
+    #[macro_export]
+    macro_rules! StringNewtypeImpl {
+        (
+        $(#[$($meta_item:tt)+])*
+        $pub:vis struct $Newtype:ident($_field_vis:vis String);
+    ) => {
+            $crate::assert_transparent_size!($Newtype, String);
+
+            impl $Newtype {
+                const LEN_BOUNDS: (usize, usize) = $crate::string_newtype_len_bounds!($([$($meta_item)+])*);
+
+                const VERSION: Option<u8> = $crate::newtype_version!($([$($meta_item)+])*);
+
+                const LOSSY: bool = $crate::string_newtype_lossy!($([$($meta_item)+])*);
+
+                /// `Validate::validate` if `custom(string_newtype(validate))` is present, otherwise `Ok(())`
+                fn check_valid(&self) -> Result<(), $crate::ValidationError> {
+                    $crate::newtype_validate!(self; $([$($meta_item)+])*)
+                }
+
+                /// `self` for the infallible constructors, panics if `check_valid` rejects it
+                fn assert_valid(self) -> Self {
+                    if let Err(err) = self.check_valid() {
+                        panic!("{err}");
+                    }
+
+                    self
+                }
+
+                fn from_owned_bytes(bytes: Vec<u8>) -> Self {
+                    Self::try_from_owned_bytes(bytes).expect("stored valid value")
+                }
+
+                fn try_from_owned_bytes(bytes: Vec<u8>) -> Result<Self, $crate::LoadError> {
+                    let bytes = $crate::version::untag(Self::VERSION, bytes)?;
+
+                    let bytes = $crate::string_newtype_compress!(@decompress bytes; $([$($meta_item)+])*);
+
+                    let s = if Self::LOSSY {
+                        String::from_utf8_lossy(&bytes).into_owned()
+                    } else {
+                        String::from_utf8(bytes).map_err(|_| $crate::LoadError::InvalidUtf8)?
+                    };
+
+                    $crate::string::check_len(&s, Self::LEN_BOUNDS)?;
+
+                    let value = Self(s);
+
+                    value.check_valid()?;
+
+                    Ok(value)
+                }
+
+                fn to_owned_bytes(&self) -> Vec<u8> {
+                    let bytes = self.0.as_bytes().to_owned();
+
+                    let bytes = $crate::string_newtype_compress!(@compress bytes; $([$($meta_item)+])*);
+
+                    $crate::version::tag(Self::VERSION, bytes)
+                }
+
+                fn to_owned_bytes_framed(&self) -> Vec<u8> {
+                    $crate::frame::frame(self.to_owned_bytes())
+                }
+
+                fn from_owned_bytes_framed(bytes: &[u8]) -> Result<(Self, &[u8]), $crate::LoadError> {
+                    let (value, rest) = $crate::frame::unframe(bytes)?;
+
+                    Ok((Self::try_from_owned_bytes(value)?, rest))
+                }
+            }
+
+            impl $crate::string::Newtype for $Newtype {
+                fn new<S>(s: S) -> Self
+                where
+                    S: Into<String> {
+                    let s = s.into();
+
+                    if let Err(err) = $crate::string::check_len(&s, Self::LEN_BOUNDS) {
+                        panic!("{err}");
+                    }
+
+                    Self(s).assert_valid()
+                }
+
+                fn as_str(&self) -> &str {
+                    self.0.as_str()
+                }
+
+                fn into_string(self) -> String {
+                    self.0
+                }
+            }
+
+            $crate::impl_byte_serde!($Newtype);
+
+            $crate::impl_serde!(@string $Newtype);
+
+            impl $crate::string::TryNew for $Newtype {
+                fn try_new<S>(s: S) -> Result<Self, $crate::string::StringError>
+                where
+                    S: Into<String>,
+                {
+                    let s = s.into();
+
+                    $crate::string::check_len(&s, Self::LEN_BOUNDS)?;
+
+                    let value = Self(s);
+
+                    value.check_valid()?;
+
+                    Ok(value)
+                }
+            }
+
+            impl From<$Newtype> for String {
+                fn from(newtype: $Newtype) -> Self {
+                    <$Newtype as $crate::string::Newtype>::into_string(newtype)
+                }
+            }
+
+            $(
+                $crate::string_newtype_derive_attrs!($Newtype, $($meta_item)+);
+            )*
+        };
+        (
+        $(#[$($meta_item:tt)+])*
+        $pub:vis struct $Newtype:ident($_field_vis:vis Arc<str>);
+    ) => {
+            $crate::assert_transparent_size!($Newtype, std::sync::Arc<str>);
+
+            // `Arc<str>` newtypes share their allocation, requires `custom(string_newtype(intern))`
+            impl $Newtype {
+                const LEN_BOUNDS: (usize, usize) = $crate::string_newtype_len_bounds!($([$($meta_item)+])*);
+
+                const VERSION: Option<u8> = $crate::newtype_version!($([$($meta_item)+])*);
+
+                const LOSSY: bool = $crate::string_newtype_lossy!($([$($meta_item)+])*);
+
+                /// `Validate::validate` if `custom(string_newtype(validate))` is present, otherwise `Ok(())`
+                fn check_valid(&self) -> Result<(), $crate::ValidationError> {
+                    $crate::newtype_validate!(self; $([$($meta_item)+])*)
+                }
+
+                /// `self` for the infallible constructors, panics if `check_valid` rejects it
+                fn assert_valid(self) -> Self {
+                    if let Err(err) = self.check_valid() {
+                        panic!("{err}");
+                    }
+
+                    self
+                }
+
+                fn from_owned_bytes(bytes: Vec<u8>) -> Self {
+                    Self::try_from_owned_bytes(bytes).expect("stored valid value")
+                }
+
+                fn try_from_owned_bytes(bytes: Vec<u8>) -> Result<Self, $crate::LoadError> {
+                    let bytes = $crate::version::untag(Self::VERSION, bytes)?;
+
+                    let bytes = $crate::string_newtype_compress!(@decompress bytes; $([$($meta_item)+])*);
+
+                    let s = if Self::LOSSY {
+                        String::from_utf8_lossy(&bytes).into_owned()
+                    } else {
+                        String::from_utf8(bytes).map_err(|_| $crate::LoadError::InvalidUtf8)?
+                    };
+
+                    $crate::string::check_len(&s, Self::LEN_BOUNDS)?;
+
+                    let value = Self(Self::intern(s));
+
+                    value.check_valid()?;
+
+                    Ok(value)
+                }
+
+                fn to_owned_bytes(&self) -> Vec<u8> {
+                    let bytes = self.0.as_bytes().to_owned();
+
+                    let bytes = $crate::string_newtype_compress!(@compress bytes; $([$($meta_item)+])*);
+
+                    $crate::version::tag(Self::VERSION, bytes)
+                }
+
+                fn to_owned_bytes_framed(&self) -> Vec<u8> {
+                    $crate::frame::frame(self.to_owned_bytes())
+                }
+
+                fn from_owned_bytes_framed(bytes: &[u8]) -> Result<(Self, &[u8]), $crate::LoadError> {
+                    let (value, rest) = $crate::frame::unframe(bytes)?;
+
+                    Ok((Self::try_from_owned_bytes(value)?, rest))
+                }
+            }
+
+            impl $crate::string::Newtype for $Newtype {
+                fn new<S>(s: S) -> Self
+                where
+                    S: Into<String> {
+                    let s = s.into();
+
+                    if let Err(err) = $crate::string::check_len(&s, Self::LEN_BOUNDS) {
+                        panic!("{err}");
+                    }
+
+                    Self(Self::intern(s)).assert_valid()
+                }
+
+                fn as_str(&self) -> &str {
+                    &self.0
+                }
+
+                fn into_string(self) -> String {
+                    self.0.to_string()
+                }
+            }
+
+            $crate::impl_byte_serde!($Newtype);
+
+            $crate::impl_serde!(@string $Newtype);
+
+            impl $crate::string::TryNew for $Newtype {
+                fn try_new<S>(s: S) -> Result<Self, $crate::string::StringError>
+                where
+                    S: Into<String>,
+                {
+                    let s = s.into();
+
+                    $crate::string::check_len(&s, Self::LEN_BOUNDS)?;
+
+                    let value = Self(Self::intern(s));
+
+                    value.check_valid()?;
+
+                    Ok(value)
+                }
+            }
+
+            impl From<$Newtype> for String {
+                fn from(newtype: $Newtype) -> Self {
+                    <$Newtype as $crate::string::Newtype>::into_string(newtype)
+                }
+            }
+
+            $(
+                $crate::string_newtype_derive_attrs!($Newtype, $($meta_item)+);
+            )*
+        };
+    }
+}
+
+pub mod address {
+    pub trait Newtype: Sized {
+        /// validates & canonicalizes `s`
+        fn try_new<S>(s: S) -> Result<Self, AddressError>
+        where
+            S: Into<String>;
+
+        fn as_str(&self) -> &str;
+
+        fn into_string(self) -> String;
+    }
+
+    #[derive(Debug, Clone, PartialEq, Eq)]
+    pub enum AddressError {
+        /// bad characters, mixed case, missing separator or checksum mismatch
+        InvalidBech32,
+        WrongHrp {
+            expected: &'static str,
+            found: String,
+        },
+        InvalidHex {
+            expected_len: usize,
+        },
+        /// rejected by a `custom(address_newtype(validate = ...))` predicate
+        Rejected,
+    }
+
+    impl std::fmt::Display for AddressError {
+        fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+            match self {
+                AddressError::InvalidBech32 => f.write_str("invalid bech32 address"),
+                AddressError::WrongHrp { expected, found } => {
+                    write!(f, "expected address prefix {expected}, found {found}")
+                }
+                AddressError::InvalidHex { expected_len } => {
+                    write!(f, "expected {expected_len} hex encoded address bytes")
+                }
+                AddressError::Rejected => f.write_str("invalid address"),
+            }
+        }
+    }
+
+    impl std::error::Error for AddressError {}
+
+    const BECH32_CHARSET: &[u8; 32] = b"qpzry9x8gf2tvdw0s3jn54khce6mua7l";
+
+    fn bech32_polymod(values: impl Iterator<Item = u8>) -> u32 {
+        const GEN: [u32; 5] = [0x3b6a57b2, 0x26508e6d, 0x1ea119fa, 0x3d4233dd, 0x2a1462b3];
+
+        values.fold(1, |chk, value| {
+            let top = chk >> 25;
+            let chk = ((chk & 0x1ffffff) << 5) ^ u32::from(value);
+
+            GEN.iter()
+                .enumerate()
+                .filter(|(i, _)| (top >> i) & 1 == 1)
+                .fold(chk, |chk, (_, gen)| chk ^ gen)
+        })
+    }
+
+    /// checks a bech32 address with human readable part `hrp`, canonical form is lowercase
+    pub fn bech32(hrp: &'static str, s: String) -> Result<String, AddressError> {
+        if s.chars().any(|c| c.is_ascii_lowercase()) && s.chars().any(|c| c.is_ascii_uppercase()) {
+            return Err(AddressError::InvalidBech32);
+        }
+
+        let s = s.to_ascii_lowercase();
+
+        let (found_hrp, data) = s.rsplit_once('1').ok_or(AddressError::InvalidBech32)?;
+
+        if found_hrp.is_empty() || data.len() < 6 {
+            return Err(AddressError::InvalidBech32);
+        }
+
+        let data = data
+            .bytes()
+            .map(|c| BECH32_CHARSET.iter().position(|&x| x == c).map(|v| v as u8))
+            .collect::<Option<Vec<u8>>>()
+            .ok_or(AddressError::InvalidBech32)?;
+
+        let hrp_expanded = found_hrp
+            .bytes()
+            .map(|c| c >> 5)
+            .chain([0])
+            .chain(found_hrp.bytes().map(|c| c & 31));
+
+        if bech32_polymod(hrp_expanded.chain(data)) != 1 {
+            return Err(AddressError::InvalidBech32);
+        }
+
+        if found_hrp != hrp {
+            return Err(AddressError::WrongHrp {
+                expected: hrp,
+                found: found_hrp.to_owned(),
+            });
+        }
+
+        Ok(s)
+    }
+
+    /// checks `len` hex encoded bytes with an optional `0x`, canonical form is lowercase with `0x`
+    pub fn hex(len: usize, s: String) -> Result<String, AddressError> {
+        let digits = s.strip_prefix("0x").unwrap_or(&s);
+
+        if digits.len() != len * 2 || !digits.bytes().all(|c| c.is_ascii_hexdigit()) {
+            return Err(AddressError::InvalidHex { expected_len: len });
+        }
+
+        Ok(format!("0x{}", digits.to_ascii_lowercase()))
+    }
+
+    /// checks `s` against a user predicate, kept as is
+    pub fn predicate(is_valid: fn(&str) -> bool, s: String) -> Result<String, AddressError> {
+        if is_valid(&s) {
+            Ok(s)
+        } else {
+            Err(AddressError::Rejected)
+        }
+    }
+
+    /// the validation from `custom(address_newtype(hrp = "..."))`, `hex = N` or `validate = path`
+    #[macro_export]
+    macro_rules! address_newtype_validate {
+        ($_s:expr;) => {
+            compile_error!("`AddressNewtypeImpl!` requires `custom(address_newtype(hrp = \"...\"))`, `hex = N` or `validate = path`")
+        };
+        ($s:expr; [custom(address_newtype(hrp = $hrp:literal))] $($_rest:tt)*) => {
+            $crate::address::bech32($hrp, $s)
+        };
+        ($s:expr; [custom(address_newtype(hex = $len:literal))] $($_rest:tt)*) => {
+            $crate::address::hex($len, $s)
+        };
+        ($s:expr; [custom(address_newtype(validate = $is_valid:path))] $($_rest:tt)*) => {
+            $crate::address::predicate($is_valid, $s)
+        };
+        ($s:expr; [$($_other_meta:tt)+] $($rest:tt)*) => {
+            $crate::address_newtype_validate!($s; $($rest)*)
+        };
+    }
+
+    #[macro_export]
+    macro_rules! address_newtype_derive_attrs {
+        ($Item:ident, custom(address_newtype(hash))) => {
+            impl std::hash::Hash for $Item {
+                fn hash<H: std::hash::Hasher>(&self, state: &mut H) {
+                    self.0.hash(state)
+                }
+            }
+        };
+        ($_Item:ident, $($_other_meta:tt)+) => {};
+    }
+
+    /// for `String` newtypes holding a validated address, both construction & loading validate
+    ///
+    /// ```
+    /// use macro_rules_attribute::derive;
+    /// use newtype_macros::prelude::*;
+    /// use newtype_macros::AddressNewtypeImpl;
+    ///
+    /// #[derive(Debug, PartialEq, AddressNewtypeImpl!)]
+    /// #[custom(address_newtype(hrp = "cosmos"))]
+    /// struct Owner(String);
+    ///
+    /// let owner = Owner::try_new("COSMOS1QQQSYQCYQ5RQWZQFPG9SCRGWPUGPZYSNRK363E").unwrap();
+    ///
+    /// assert_eq!(owner.as_str(), "cosmos1qqqsyqcyq5rqwzqfpg9scrgwpugpzysnrk363e");
+    /// assert!(Owner::try_new("osmo1qqqsyqcyq5rqwzqfpg9scrgwpugpzysntdz28t").is_err());
+    /// ```
+    #[macro_export]
+    macro_rules! AddressNewtypeImpl {
+        (
+        $(#[$($meta_item:tt)+])*
+        $pub:vis struct $Newtype:ident($_field_vis:vis String);
+    ) => {
+            $crate::assert_transparent_size!($Newtype, String);
+
+            impl $Newtype {
+                const VERSION: Option<u8> = $crate::newtype_version!($([$($meta_item)+])*);
+
+                fn validate(s: String) -> Result<String, $crate::address::AddressError> {
+                    $crate::address_newtype_validate!(s; $([$($meta_item)+])*)
+                }
+
+                fn from_owned_bytes(bytes: Vec<u8>) -> Self {
+                    Self::try_from_owned_bytes(bytes).expect("stored valid value")
+                }
+
+                fn try_from_owned_bytes(bytes: Vec<u8>) -> Result<Self, $crate::LoadError> {
+                    let bytes = $crate::version::untag(Self::VERSION, bytes)?;
+
+                    let s = String::from_utf8(bytes).map_err(|_| $crate::LoadError::InvalidUtf8)?;
+
+                    Ok(Self(Self::validate(s)?))
+                }
+
+                fn to_owned_bytes(&self) -> Vec<u8> {
+                    let bytes = self.0.as_bytes().to_owned();
+
+                    $crate::version::tag(Self::VERSION, bytes)
+                }
+
+                fn to_owned_bytes_framed(&self) -> Vec<u8> {
+                    $crate::frame::frame(self.to_owned_bytes())
+                }
+
+                fn from_owned_bytes_framed(bytes: &[u8]) -> Result<(Self, &[u8]), $crate::LoadError> {
+                    let (value, rest) = $crate::frame::unframe(bytes)?;
+
+                    Ok((Self::try_from_owned_bytes(value)?, rest))
+                }
+            }
+
+            impl $crate::address::Newtype for $Newtype {
+                fn try_new<S>(s: S) -> Result<Self, $crate::address::AddressError>
+                where
+                    S: Into<String> {
+                    Self::validate(s.into()).map(Self)
+                }
+
+                fn as_str(&self) -> &str {
+                    self.0.as_str()
+                }
+
+                fn into_string(self) -> String {
+                    self.0
+                }
+            }
+
+            $crate::impl_byte_serde!($Newtype);
+
+            $(
+                $crate::address_newtype_derive_attrs!($Newtype, $($meta_item)+);
+            )*
+        };
+    }
+}
+
+pub mod array {
+    pub trait Newtype: Sized {
+        type Array;
+
+        fn as_slice(&self) -> &[u8];
+
+        fn into_array(self) -> Self::Array;
+
+        #[must_use]
+        fn from_inner(inner: Self::Array) -> Self;
+
+        /// accepts upper & lowercase hex of exactly the array length
+        fn from_hex(hex: &str) -> Result<Self, HexError>;
+
+        fn len(&self) -> usize {
+            self.as_slice().len()
+        }
+
+        fn is_empty(&self) -> bool {
+            self.as_slice().is_empty()
+        }
+
+        /// lowercase hex
+        fn to_hex(&self) -> String {
+            to_hex(self.as_slice())
+        }
+    }
+
+    #[derive(Debug, PartialEq, Eq)]
+    pub struct HexError {
+        pub expected_len: usize,
+    }
+
+    impl std::fmt::Display for HexError {
+        fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+            write!(f, "expected {} hex encoded bytes", self.expected_len)
+        }
+    }
+
+    impl std::error::Error for HexError {}
+
+    /// lowercase hex
+    pub fn to_hex(bytes: &[u8]) -> String {
+        const DIGITS: &[u8; 16] = b"0123456789abcdef";
+
+        let mut hex = String::with_capacity(bytes.len() * 2);
+
+        for byte in bytes {
+            hex.push(DIGITS[(byte >> 4) as usize] as char);
+            hex.push(DIGITS[(byte & 0xf) as usize] as char);
+        }
+
+        hex
+    }
+
+    /// accepts upper & lowercase hex, `None` on odd length or non-hex digits
+    pub fn from_hex(hex: &str) -> Option<Vec<u8>> {
+        fn digit(c: u8) -> Option<u8> {
+            match c {
+                b'0'..=b'9' => Some(c - b'0'),
+                b'a'..=b'f' => Some(c - b'a' + 10),
+                b'A'..=b'F' => Some(c - b'A' + 10),
+                _ => None,
+            }
+        }
+
+        if !hex.len().is_multiple_of(2) {
+            return None;
+        }
+
+        hex.as_bytes()
+            .chunks_exact(2)
+            .map(|pair| Some(digit(pair[0])? << 4 | digit(pair[1])?))
+            .collect()
+    }
+
+    #[macro_export]
+    macro_rules! array_newtype_derive_attrs {
+        ($Item:ident, custom(array_newtype(hash))) => {
+            impl std::hash::Hash for $Item {
+                fn hash<H: std::hash::Hasher>(&self, state: &mut H) {
+                    self.0.hash(state)
+                }
+            }
+        };
+        ($_Item:ident, custom(array_newtype(versioned $(= $_version:literal)?))) => {};
+        ($_Item:ident, custom(array_newtype($($unknown:tt)*))) => {
+            compile_error!(concat!("unknown `array_newtype` attribute: `", stringify!($($unknown)*), "`"));
+        };
+        ($_Item:ident, $($_other_meta:tt)+) => {};
+    }
+
+    /// for `[u8; N]` newtypes, stored as the raw bytes and keyed by lowercase hex
+    ///
+    /// ```
+    /// use macro_rules_attribute::derive;
+    /// use newtype_macros::prelude::*;
+    /// use newtype_macros::{ArrayNewtypeImpl, ItemStoreImpl};
+    ///
+    /// #[derive(Debug, PartialEq, ArrayNewtypeImpl!, ItemStoreImpl!)]
+    /// #[custom(item_store(key = "root"))]
+    /// struct Root([u8; 4]);
+    ///
+    /// let mut storage = MemoryStorage::default();
+    ///
+    /// Root([1, 2, 3, 4]).save(&mut storage);
+    ///
+    /// assert_eq!(storage.get(b"root"), Some(vec![1, 2, 3, 4]));
+    /// ```
+    #[macro_export]
+    macro_rules! ArrayNewtypeImpl {
+        (
+        $(#[$($meta_item:tt)+])*
+        $pub:vis struct $Newtype:ident($_field_vis:vis [u8; $len:expr]);
+    ) => {
+            $crate::assert_transparent_size!($Newtype, [u8; $len]);
+
+            impl $Newtype {
+                const VERSION: Option<u8> = $crate::newtype_version!($([$($meta_item)+])*);
+                /// stored length of the value, before any version byte
+                pub const BYTE_LEN: usize = $len;
+
+                fn from_owned_bytes(bytes: Vec<u8>) -> Self {
+                    Self::try_from_owned_bytes(bytes).expect("stored valid value")
+                }
+
+                fn try_from_owned_bytes(bytes: Vec<u8>) -> Result<Self, $crate::LoadError> {
+                    Self::try_from_borrowed_bytes(&bytes)
+                }
+
+                fn from_borrowed_bytes(bytes: &[u8]) -> Self {
+                    Self::try_from_borrowed_bytes(bytes).expect("stored valid value")
+                }
+
+                fn try_from_borrowed_bytes(bytes: &[u8]) -> Result<Self, $crate::LoadError> {
+                    let bytes = $crate::version::untag_slice(Self::VERSION, bytes)?;
+
+                    TryFrom::try_from(bytes).map(Self).map_err(|_| {
+                        $crate::LoadError::ByteLength {
+                            expected: Self::BYTE_LEN,
+                            found: bytes.len(),
+                        }
+                    })
+                }
+
+                fn to_owned_bytes(&self) -> Vec<u8> {
+                    let bytes = self.0.to_vec();
+
+                    $crate::version::tag(Self::VERSION, bytes)
+                }
+
+                fn to_owned_bytes_framed(&self) -> Vec<u8> {
+                    $crate::frame::frame(self.to_owned_bytes())
+                }
+
+                fn from_owned_bytes_framed(bytes: &[u8]) -> Result<(Self, &[u8]), $crate::LoadError> {
+                    let (value, rest) = $crate::frame::unframe(bytes)?;
+
+                    Ok((Self::try_from_owned_bytes(value)?, rest))
+                }
+            }
+
+            impl $crate::array::Newtype for $Newtype {
+                type Array = [u8; $len];
+
+                fn as_slice(&self) -> &[u8] {
+                    &self.0
+                }
+
+                fn into_array(self) -> Self::Array {
+                    self.0
+                }
+
+                fn from_inner(inner: Self::Array) -> Self {
+                    Self(inner)
+                }
+
+                fn from_hex(hex: &str) -> Result<Self, $crate::array::HexError> {
+                    $crate::array::from_hex(hex)
+                        .and_then(|bytes| bytes.try_into().ok())
+                        .map(Self)
+                        .ok_or($crate::array::HexError { expected_len: $len })
+                }
+            }
+
+            impl AsRef<[u8]> for $Newtype {
+                fn as_ref(&self) -> &[u8] {
+                    &self.0
+                }
+            }
+
+            impl $crate::map::ToMapKey for $Newtype {
+                fn to_map_key(&self) -> String {
+                    $crate::array::to_hex(&self.0)
+                }
+            }
+
+            impl $crate::map::FromMapKey for $Newtype {
+                fn from_map_key(key: &str) -> Option<Self> {
+                    $crate::array::from_hex(key)?.try_into().ok().map(Self)
+                }
+            }
+
+            $crate::impl_byte_serde!($Newtype, fixed_len = Self::BYTE_LEN + Self::VERSION.is_some() as usize);
+
+            $(
+                $crate::array_newtype_derive_attrs!($Newtype, $($meta_item)+);
+            )*
+        };
+    }
+}
+
+pub mod enumeration {
+    pub trait Newtype: Sized {
+        fn discriminant(&self) -> u8;
+
+        fn from_discriminant(discriminant: u8) -> Option<Self>;
+    }
+
+    #[macro_export]
+    macro_rules! enum_newtype_derive_attrs {
+        ($_Item:ident, $($_other_meta:tt)+) => {};
+    }
+
+    /// for fieldless `#[repr(u8)]` enums, stored as their single byte discriminant
+    ///
+    /// ```
+    /// use macro_rules_attribute::derive;
+    /// use newtype_macros::prelude::*;
+    /// use newtype_macros::{EnumNewtypeImpl, ItemStoreImpl};
+    ///
+    /// #[derive(Debug, PartialEq, EnumNewtypeImpl!, ItemStoreImpl!)]
+    /// #[custom(item_store(key = "status"))]
+    /// #[repr(u8)]
+    /// enum Status {
+    ///     Pending,
+    ///     Active = 4,
+    /// }
+    ///
+    /// let mut storage = MemoryStorage::default();
+    ///
+    /// Status::Active.save(&mut storage);
+    ///
+    /// assert_eq!(storage.get(b"status"), Some(vec![4]));
+    /// assert_eq!(Status::load(&storage), Some(Status::Active));
+    /// ```
+    #[macro_export]
+    macro_rules! EnumNewtypeImpl {
+        (
+        $(#[$($meta_item:tt)+])*
+        $pub:vis enum $Enum:ident {
+            $($Variant:ident $(= $_discriminant:expr)?),* $(,)?
+        }
+    ) => {
+            impl $Enum {
+                const VERSION: Option<u8> = $crate::newtype_version!($([$($meta_item)+])*);
+
+                fn from_owned_bytes(bytes: Vec<u8>) -> Self {
+                    Self::try_from_owned_bytes(bytes).expect("stored valid value")
+                }
+
+                fn try_from_owned_bytes(bytes: Vec<u8>) -> Result<Self, $crate::LoadError> {
+                    let bytes = $crate::version::untag(Self::VERSION, bytes)?;
+
+                    let [discriminant] = bytes[..] else {
+                        return Err($crate::LoadError::ByteLength {
+                            expected: 1,
+                            found: bytes.len(),
+                        });
+                    };
+
+                    <Self as $crate::enumeration::Newtype>::from_discriminant(discriminant)
+                        .ok_or($crate::LoadError::InvalidDiscriminant(discriminant))
+                }
+
+                fn to_owned_bytes(&self) -> Vec<u8> {
+                    let bytes = vec![<Self as $crate::enumeration::Newtype>::discriminant(self)];
+
+                    $crate::version::tag(Self::VERSION, bytes)
+                }
+
+                fn to_owned_bytes_framed(&self) -> Vec<u8> {
+                    $crate::frame::frame(self.to_owned_bytes())
+                }
+
+                fn from_owned_bytes_framed(bytes: &[u8]) -> Result<(Self, &[u8]), $crate::LoadError> {
+                    let (value, rest) = $crate::frame::unframe(bytes)?;
+
+                    Ok((Self::try_from_owned_bytes(value)?, rest))
+                }
+            }
+
+            impl $crate::enumeration::Newtype for $Enum {
+                fn discriminant(&self) -> u8 {
+                    match self {
+                        $(Self::$Variant => Self::$Variant as u8,)*
+                    }
+                }
+
+                fn from_discriminant(discriminant: u8) -> Option<Self> {
+                    $(
+                        if discriminant == Self::$Variant as u8 {
+                            return Some(Self::$Variant);
+                        }
+                    )*
+
+                    None
+                }
+            }
+
+            impl $crate::map::ToMapKey for $Enum {
+                fn to_map_key(&self) -> String {
+                    $crate::map::ToMapKey::to_map_key(
+                        &<Self as $crate::enumeration::Newtype>::discriminant(self),
+                    )
+                }
+            }
+
+            impl $crate::map::FromMapKey for $Enum {
+                fn from_map_key(key: &str) -> Option<Self> {
+                    $crate::map::FromMapKey::from_map_key(key)
+                        .and_then(<Self as $crate::enumeration::Newtype>::from_discriminant)
+                }
+            }
+
+            $crate::impl_byte_serde!($Enum);
+
+            $(
+                $crate::enum_newtype_derive_attrs!($Enum, $($meta_item)+);
+            )*
+        };
+    }
+}
+
+pub mod option {
+    pub trait Newtype: Sized {
+        type Inner;
+
+        fn get(self) -> Option<Self::Inner>;
+
+        fn is_none(&self) -> bool;
+    }
+
+    #[macro_export]
+    macro_rules! option_newtype_derive_attrs {
+        ($_Item:ident, $($_other_meta:tt)+) => {};
+    }
+
+    /// for `Option<uint>` newtypes, stored as a presence byte followed by the big-endian inner
+    /// value, so an explicit `None` is distinguishable from nothing stored
+    ///
+    /// ```
+    /// use macro_rules_attribute::derive;
+    /// use newtype_macros::prelude::*;
+    /// use newtype_macros::{ItemStoreImpl, OptionNewtypeImpl};
+    ///
+    /// #[derive(Debug, PartialEq, OptionNewtypeImpl!, ItemStoreImpl!)]
+    /// struct MaybeFee(Option<u64>);
+    ///
+    /// let mut storage = MemoryStorage::default();
+    ///
+    /// MaybeFee(None).save(&mut storage);
+    ///
+    /// assert!(MaybeFee::exists(&storage));
+    /// assert_eq!(MaybeFee::load(&storage).unwrap().get(), None);
+    /// ```
+    #[macro_export]
+    macro_rules! OptionNewtypeImpl {
+        (
+        $(#[$($meta_item:tt)+])*
+        $pub:vis struct $Newtype:ident($_field_vis:vis Option<$Uint:ty>);
+    ) => {
+            $crate::assert_transparent_size!($Newtype, Option<$Uint>);
+
+            impl $Newtype {
+                const VERSION: Option<u8> = $crate::newtype_version!($([$($meta_item)+])*);
+
+                fn from_owned_bytes(bytes: Vec<u8>) -> Self {
+                    Self::try_from_owned_bytes(bytes).expect("stored valid value")
+                }
+
+                fn try_from_owned_bytes(bytes: Vec<u8>) -> Result<Self, $crate::LoadError> {
+                    let bytes = $crate::version::untag(Self::VERSION, bytes)?;
+
+                    match bytes.split_first() {
+                        Some((0, [])) => Ok(Self(None)),
+                        Some((1, inner)) => {
+                            let be_bytes = TryFrom::try_from(inner).map_err(|_| {
+                                $crate::LoadError::ByteLength {
+                                    expected: 1 + std::mem::size_of::<<$Uint as $crate::FixedWidthBytes>::Bytes>(),
+                                    found: bytes.len(),
+                                }
+                            })?;
+
+                            <$Uint as $crate::FixedWidthBytes>::from_fixed_width_bytes(be_bytes)
+                                .map(|inner| Self(Some(inner)))
+                                .ok_or($crate::LoadError::Overflow)
+                        }
+                        Some((0, _)) => Err($crate::LoadError::ByteLength {
+                            expected: 1,
+                            found: bytes.len(),
+                        }),
+                        Some((&tag, _)) => Err($crate::LoadError::InvalidDiscriminant(tag)),
+                        None => Err($crate::LoadError::ByteLength {
+                            expected: 1,
+                            found: 0,
+                        }),
+                    }
+                }
+
+                fn to_owned_bytes(&self) -> Vec<u8> {
+                    let bytes = match self.0 {
+                        None => vec![0],
+                        Some(inner) => {
+                            let mut bytes = vec![1];
+                            bytes.extend_from_slice(&$crate::FixedWidthBytes::to_fixed_width_bytes(inner));
+                            bytes
+                        }
+                    };
+
+                    $crate::version::tag(Self::VERSION, bytes)
+                }
+
+                fn to_owned_bytes_framed(&self) -> Vec<u8> {
+                    $crate::frame::frame(self.to_owned_bytes())
+                }
+
+                fn from_owned_bytes_framed(bytes: &[u8]) -> Result<(Self, &[u8]), $crate::LoadError> {
+                    let (value, rest) = $crate::frame::unframe(bytes)?;
+
+                    Ok((Self::try_from_owned_bytes(value)?, rest))
+                }
+            }
+
+            impl $crate::option::Newtype for $Newtype {
+                type Inner = $Uint;
+
+                fn get(self) -> Option<Self::Inner> {
+                    self.0
+                }
+
+                fn is_none(&self) -> bool {
+                    self.0.is_none()
+                }
+            }
+
+            $crate::impl_byte_serde!($Newtype);
+
+            $(
+                $crate::option_newtype_derive_attrs!($Newtype, $($meta_item)+);
+            )*
+        };
+    }
+}
+
+pub mod vec {
+    pub trait Newtype: Sized {
+        type Element;
+
+        fn as_slice(&self) -> &[Self::Element];
+
+        fn into_vec(self) -> Vec<Self::Element>;
+
+        fn len(&self) -> usize {
+            self.as_slice().len()
+        }
+
+        fn is_empty(&self) -> bool {
+            self.as_slice().is_empty()
+        }
+    }
+
+    #[macro_export]
+    macro_rules! vec_newtype_derive_attrs {
+        ($_Item:ident, $($_other_meta:tt)+) => {};
+    }
+
+    /// for `Vec<T>` newtypes where `T: ByteSerde`, stored as the `u32` big-endian element count
+    /// followed by each framed element, so an empty vec is still distinguishable from nothing stored
+    ///
+    /// ```
+    /// use macro_rules_attribute::derive;
+    /// use newtype_macros::prelude::*;
+    /// use newtype_macros::{ItemStoreImpl, UintNewtypeImpl, VecNewtypeImpl};
+    ///
+    /// #[derive(Debug, PartialEq, UintNewtypeImpl!)]
+    /// struct Id(u32);
+    ///
+    /// #[derive(Debug, PartialEq, VecNewtypeImpl!, ItemStoreImpl!)]
+    /// struct Ids(Vec<Id>);
+    ///
+    /// let mut storage = MemoryStorage::default();
+    ///
+    /// Ids(vec![Id(1), Id(2)]).save(&mut storage);
+    ///
+    /// assert_eq!(Ids::load(&storage), Some(Ids(vec![Id(1), Id(2)])));
+    /// ```
+    #[macro_export]
+    macro_rules! VecNewtypeImpl {
+        (
+        $(#[$($meta_item:tt)+])*
+        $pub:vis struct $Newtype:ident($_field_vis:vis Vec<$Elem:ty>);
+    ) => {
+            $crate::assert_transparent_size!($Newtype, Vec<$Elem>);
+
+            impl $Newtype {
+                const VERSION: Option<u8> = $crate::newtype_version!($([$($meta_item)+])*);
+
+                fn from_owned_bytes(bytes: Vec<u8>) -> Self {
+                    Self::try_from_owned_bytes(bytes).expect("stored valid value")
+                }
+
+                fn try_from_owned_bytes(bytes: Vec<u8>) -> Result<Self, $crate::LoadError> {
+                    let bytes = $crate::version::untag(Self::VERSION, bytes)?;
+
+                    let Some((count, mut rest)) = bytes.split_first_chunk::<4>() else {
+                        return Err($crate::LoadError::ByteLength {
+                            expected: 4,
+                            found: bytes.len(),
+                        });
+                    };
+
+                    let count = u32::from_be_bytes(*count) as usize;
+
+                    let mut elems = Vec::with_capacity(count.min(rest.len() / 4));
+
+                    for _ in 0..count {
+                        let (elem, next) = $crate::frame::unframe(rest)?;
+                        elems.push(<$Elem as $crate::ByteSerde>::try_from_owned_bytes(elem)?);
+                        rest = next;
+                    }
+
+                    if !rest.is_empty() {
+                        return Err($crate::LoadError::ByteLength {
+                            expected: bytes.len() - rest.len(),
+                            found: bytes.len(),
+                        });
+                    }
+
+                    Ok(Self(elems))
+                }
+
+                fn to_owned_bytes(&self) -> Vec<u8> {
+                    let count = u32::try_from(self.0.len()).expect("element count fits in u32");
+
+                    let mut bytes = count.to_be_bytes().to_vec();
+
+                    for elem in &self.0 {
+                        bytes.extend($crate::frame::frame(<$Elem as $crate::ByteSerde>::to_owned_bytes(elem)));
+                    }
+
+                    $crate::version::tag(Self::VERSION, bytes)
+                }
+
+                fn to_owned_bytes_framed(&self) -> Vec<u8> {
+                    $crate::frame::frame(self.to_owned_bytes())
+                }
+
+                fn from_owned_bytes_framed(bytes: &[u8]) -> Result<(Self, &[u8]), $crate::LoadError> {
+                    let (value, rest) = $crate::frame::unframe(bytes)?;
+
+                    Ok((Self::try_from_owned_bytes(value)?, rest))
+                }
+            }
+
+            impl $crate::vec::Newtype for $Newtype {
+                type Element = $Elem;
+
+                fn as_slice(&self) -> &[Self::Element] {
+                    &self.0
+                }
+
+                fn into_vec(self) -> Vec<Self::Element> {
+                    self.0
+                }
+            }
+
+            $crate::impl_byte_serde!($Newtype);
+
+            $(
+                $crate::vec_newtype_derive_attrs!($Newtype, $($meta_item)+);
+            )*
+        };
+    }
+}
+
+pub mod map_field {
+    pub trait Newtype: Sized {
+        type Map;
+
+        fn as_map(&self) -> &Self::Map;
+
+        fn into_map(self) -> Self::Map;
+    }
+
+    #[macro_export]
+    macro_rules! map_field_newtype_derive_attrs {
+        ($_Item:ident, $($_other_meta:tt)+) => {};
+    }
+
+    /// for `BTreeMap<K, V>` / `HashMap<K, V>` newtypes where `K: ByteSerde` & `V: ByteSerde`, stored
+    /// as the `u32` big-endian entry count followed by each framed key & value, sorted by encoded
+    /// key so equal maps always store equal bytes
+    ///
+    /// ```
+    /// use std::collections::BTreeMap;
+    ///
+    /// use macro_rules_attribute::derive;
+    /// use newtype_macros::prelude::*;
+    /// use newtype_macros::{ItemStoreImpl, MapFieldNewtypeImpl};
+    ///
+    /// #[derive(Debug, PartialEq, MapFieldNewtypeImpl!, ItemStoreImpl!)]
+    /// struct Meta(BTreeMap<String, String>);
+    ///
+    /// let mut storage = MemoryStorage::default();
+    ///
+    /// let meta = Meta([("a".to_owned(), "1".to_owned())].into());
+    ///
+    /// meta.save(&mut storage);
+    ///
+    /// assert_eq!(Meta::load(&storage), Some(meta));
+    /// ```
+    #[macro_export]
+    macro_rules! MapFieldNewtypeImpl {
+        (
+        $(#[$($meta_item:tt)+])*
+        $pub:vis struct $Newtype:ident($_field_vis:vis $Map:ident<$K:ty, $V:ty>);
+    ) => {
+            $crate::assert_transparent_size!($Newtype, $Map<$K, $V>);
+
+            impl $Newtype {
+                const VERSION: Option<u8> = $crate::newtype_version!($([$($meta_item)+])*);
+
+                fn from_owned_bytes(bytes: Vec<u8>) -> Self {
+                    Self::try_from_owned_bytes(bytes).expect("stored valid value")
+                }
+
+                fn try_from_owned_bytes(bytes: Vec<u8>) -> Result<Self, $crate::LoadError> {
+                    let bytes = $crate::version::untag(Self::VERSION, bytes)?;
+
+                    let Some((count, mut rest)) = bytes.split_first_chunk::<4>() else {
+                        return Err($crate::LoadError::ByteLength {
+                            expected: 4,
+                            found: bytes.len(),
+                        });
+                    };
+
+                    let count = u32::from_be_bytes(*count) as usize;
+
+                    let mut entries = Vec::with_capacity(count.min(rest.len() / 8));
+
+                    for _ in 0..count {
+                        let (key, next) = $crate::frame::unframe(rest)?;
+                        let (value, next) = $crate::frame::unframe(next)?;
+
+                        entries.push((
+                            <$K as $crate::ByteSerde>::try_from_owned_bytes(key)?,
+                            <$V as $crate::ByteSerde>::try_from_owned_bytes(value)?,
+                        ));
+
+                        rest = next;
+                    }
+
+                    if !rest.is_empty() {
+                        return Err($crate::LoadError::ByteLength {
+                            expected: bytes.len() - rest.len(),
+                            found: bytes.len(),
+                        });
+                    }
+
+                    Ok(Self(entries.into_iter().collect()))
+                }
+
+                fn to_owned_bytes(&self) -> Vec<u8> {
+                    let mut entries: Vec<_> = self
+                        .0
+                        .iter()
+                        .map(|(key, value)| {
+                            (
+                                <$K as $crate::ByteSerde>::to_owned_bytes(key),
+                                <$V as $crate::ByteSerde>::to_owned_bytes(value),
+                            )
+                        })
+                        .collect();
 
-    pub trait ClearAtOrLoadAlwaysAt {}
+                    entries.sort();
 
-    #[macro_export]
-    macro_rules! store_map_derive_attrs {
-        ($Item:ident, custom(map_store(key, $key:ty))) => {
-            impl $crate::map::MapKeyType for $Item {
-                type MapKeyType = $key;
-            }
+                    let count = u32::try_from(entries.len()).expect("entry count fits in u32");
 
-            impl $crate::map::Store for $Item {
-                fn load_at(
-                    storage: &dyn $crate::ReadonlyStorage,
-                    key: Self::MapKeyType,
-                ) -> Option<Self> {
-                    storage
-                        .get(Self::map_key(key).as_bytes())
-                        .map(Self::from_owned_bytes)
-                }
+                    let mut bytes = count.to_be_bytes().to_vec();
 
-                fn save_at(&self, storage: &mut dyn $crate::MutableStorage, key: Self::MapKeyType) {
-                    storage.set(
-                        Self::map_key(key).as_bytes(),
-                        self.to_owned_bytes().as_slice(),
-                    );
+                    for (key, value) in entries {
+                        bytes.extend($crate::frame::frame(key));
+                        bytes.extend($crate::frame::frame(value));
+                    }
+
+                    $crate::version::tag(Self::VERSION, bytes)
                 }
-            }
-        };
-        ($Item:ident, custom(map_store(always))) => {
-            impl $crate::map::ClearAtOrLoadAlwaysAt for $Item {}
 
-            impl $crate::map::LoadAlwaysAt for $Item {
-                fn load_always_at(
-                    storage: &dyn $crate::ReadonlyStorage,
-                    key: Self::MapKeyType,
-                ) -> Self {
-                    Self::load_at(storage, key).expect("always present in storage")
+                fn to_owned_bytes_framed(&self) -> Vec<u8> {
+                    $crate::frame::frame(self.to_owned_bytes())
                 }
-            }
-        };
-        ($Item:ident, custom(map_store(clear))) => {
-            impl $crate::map::ClearAtOrLoadAlwaysAt for $Item {}
 
-            impl $crate::map::ClearAt for $Item {
-                fn clear_at(storage: &mut dyn $crate::MutableStorage, key: Self::MapKeyType) {
-                    storage.clear(Self::map_key(key).as_bytes());
+                fn from_owned_bytes_framed(bytes: &[u8]) -> Result<(Self, &[u8]), $crate::LoadError> {
+                    let (value, rest) = $crate::frame::unframe(bytes)?;
+
+                    Ok((Self::try_from_owned_bytes(value)?, rest))
                 }
             }
-        };
-        ($_Item:ident, $($_other_meta:tt)+) => {};
-    }
 
-    #[macro_export]
-    macro_rules! MapStoreImpl {
-        (
-        $(#[$($meta_item:tt)+])*
-        $pub:vis struct $Item:ident($Inner:ident);
-    ) => {
-            impl $Item {
-                $crate::paste! {
-                    const KEY_PREFIX: &'static str = concat!(module_path!(), "::", stringify!([< $Item:snake _ $Inner:snake >]));
-                }
+            impl $crate::map_field::Newtype for $Newtype {
+                type Map = $Map<$K, $V>;
 
-                fn map_key(key: <Self as $crate::map::MapKeyType>::MapKeyType) -> String {
-                    use $crate::map::IntoMapKey;
+                fn as_map(&self) -> &Self::Map {
+                    &self.0
+                }
 
-                    let mut full_key = Self::KEY_PREFIX.to_owned();
-                    full_key.push_str("::");
-                    full_key.push_str(key.into_map_key().as_str());
-                    full_key
+                fn into_map(self) -> Self::Map {
+                    self.0
                 }
             }
 
+            $crate::impl_byte_serde!($Newtype);
+
             $(
-                $crate::store_map_derive_attrs!($Item, $($meta_item)+);
+                $crate::map_field_newtype_derive_attrs!($Newtype, $($meta_item)+);
             )*
         };
     }
 }
 
-pub mod non_zero {
-    pub trait Newtype: Sized {
-        type PrimitiveInner;
-        type NonZeroInner;
-
-        fn non_zero(self) -> Self::NonZeroInner;
+#[cfg(feature = "bignum")]
+pub mod u256 {
+    /// 256-bit unsigned integer from `primitive-types`
+    pub use primitive_types::U256;
 
-        fn get(self) -> Self::PrimitiveInner;
-    }
+    pub trait Newtype: Sized {
+        fn get(&self) -> U256;
 
-    pub trait FromNonZero: Sized + Newtype {
-        fn from_non_zero<NonZero>(non_zero: NonZero) -> Self
-        where
-            Self::NonZeroInner: From<NonZero>;
-    }
+        fn checked_add(self, rhs: Self) -> Option<Self>;
 
-    pub trait CheckedNew: Sized + Newtype {
-        fn checked_new<T>(t: T) -> Option<Self>
-        where
-            Self::PrimitiveInner: From<T>;
+        fn checked_sub(self, rhs: Self) -> Option<Self>;
     }
 
     #[macro_export]
-    macro_rules! non_zero_newtype_derive_attrs {
-        ($Item:ident, custom(non_zero_newtype(from_non_zero))) => {
-            impl $crate::non_zero::FromNonZero for $Item {
-                fn from_non_zero<NonZero>(non_zero: NonZero) -> Self
-                where
-                    Self::NonZeroInner: From<NonZero>,
-                {
-                    Self(Self::NonZeroInner::from(non_zero))
-                }
-            }
-        };
-        ($Item:ident, custom(non_zero_newtype(checked_new))) => {
-            impl $crate::non_zero::CheckedNew for $Item {
-                fn checked_new<T>(t: T) -> Option<Self>
-                where
-                    Self::PrimitiveInner: From<T>,
-                {
-                    Self::NonZeroInner::new(Self::PrimitiveInner::from(t)).map(Self)
-                }
-            }
-        };
+    macro_rules! u256_newtype_derive_attrs {
         ($_Item:ident, $($_other_meta:tt)+) => {};
     }
 
+    /// for `U256` newtypes, stored as 32 big-endian bytes
+    ///
+    /// ```
+    /// use macro_rules_attribute::derive;
+    /// use newtype_macros::prelude::*;
+    /// use newtype_macros::u256::U256;
+    /// use newtype_macros::{ItemStoreImpl, U256NewtypeImpl};
+    ///
+    /// #[derive(Debug, PartialEq, U256NewtypeImpl!, ItemStoreImpl!)]
+    /// struct TotalSupply(U256);
+    ///
+    /// let mut storage = MemoryStorage::default();
+    ///
+    /// TotalSupply(U256::from(7u8)).save(&mut storage);
+    ///
+    /// assert_eq!(TotalSupply::load(&storage), Some(TotalSupply(U256::from(7u8))));
+    /// ```
     #[macro_export]
-    macro_rules! NonZeroNewtypeImpl {
+    macro_rules! U256NewtypeImpl {
         (
         $(#[$($meta_item:tt)+])*
-        $pub:vis struct $Newtype:ident($NonZeroInteger:path);
+        $pub:vis struct $Newtype:ident($_field_vis:vis $_U256:ty);
     ) => {
+            $crate::assert_transparent_size!($Newtype, $crate::u256::U256);
+
             impl $Newtype {
+                const VERSION: Option<u8> = $crate::newtype_version!($([$($meta_item)+])*);
+                /// stored length of the value, before any version byte
+                pub const BYTE_LEN: usize = 32;
+
                 fn from_owned_bytes(bytes: Vec<u8>) -> Self {
-                    let be_bytes =
-                        TryFrom::try_from(bytes).expect("always stored correct amount of bytes");
+                    Self::try_from_owned_bytes(bytes).expect("stored valid value")
+                }
+
+                fn try_from_owned_bytes(bytes: Vec<u8>) -> Result<Self, $crate::LoadError> {
+                    Self::try_from_borrowed_bytes(&bytes)
+                }
 
-                    let primative = <Self as $crate::non_zero::Newtype>::PrimitiveInner::from_be_bytes(be_bytes);
+                fn from_borrowed_bytes(bytes: &[u8]) -> Self {
+                    Self::try_from_borrowed_bytes(bytes).expect("stored valid value")
+                }
 
-                    let non_zero = <Self as $crate::non_zero::Newtype>::NonZeroInner::new(primative).expect("saved primative > 0");
+                fn try_from_borrowed_bytes(bytes: &[u8]) -> Result<Self, $crate::LoadError> {
+                    let bytes = $crate::version::untag_slice(Self::VERSION, bytes)?;
 
-                    Self(non_zero)
+                    TryFrom::try_from(bytes)
+                        .map(|bytes: [u8; 32]| Self($crate::u256::U256::from_big_endian(&bytes)))
+                        .map_err(|_| $crate::LoadError::ByteLength {
+                            expected: Self::BYTE_LEN,
+                            found: bytes.len(),
+                        })
                 }
 
                 fn to_owned_bytes(&self) -> Vec<u8> {
-                    self.0.get().to_be_bytes().to_vec()
+                    let bytes = self.0.to_big_endian().to_vec();
+
+                    $crate::version::tag(Self::VERSION, bytes)
                 }
-            }
 
-            impl $crate::non_zero::Newtype for $Newtype {
-                type NonZeroInner = $NonZeroInteger;
-                type PrimitiveInner = <Self::NonZeroInner as $crate::Primitive>::Primative;
+                fn to_owned_bytes_framed(&self) -> Vec<u8> {
+                    $crate::frame::frame(self.to_owned_bytes())
+                }
+
+                fn from_owned_bytes_framed(bytes: &[u8]) -> Result<(Self, &[u8]), $crate::LoadError> {
+                    let (value, rest) = $crate::frame::unframe(bytes)?;
+
+                    Ok((Self::try_from_owned_bytes(value)?, rest))
+                }
+            }
 
-                fn non_zero(self) -> Self::NonZeroInner {
+            impl $crate::u256::Newtype for $Newtype {
+                fn get(&self) -> $crate::u256::U256 {
                     self.0
                 }
 
-                fn get(self) -> Self::PrimitiveInner {
-                    self.0.get()
+                fn checked_add(self, rhs: Self) -> Option<Self> {
+                    self.0.checked_add(rhs.0).map(Self)
+                }
+
+                fn checked_sub(self, rhs: Self) -> Option<Self> {
+                    self.0.checked_sub(rhs.0).map(Self)
                 }
             }
 
+            $crate::impl_byte_serde!($Newtype, fixed_len = Self::BYTE_LEN + Self::VERSION.is_some() as usize);
+
             $(
-                $crate::non_zero_newtype_derive_attrs!($Newtype, $($meta_item)+);
+                $crate::u256_newtype_derive_attrs!($Newtype, $($meta_item)+);
             )*
         };
     }
 }
 
-pub mod uint {
+pub mod time {
+    use std::time::Duration;
+
+    /// `custom(uint_newtype(validate))` applies, `from_nanos` panics on a rejected value and the
+    /// fallible constructors return `None`
     pub trait Newtype: Sized {
-        type PrimitiveInner;
-        type NonZeroInner;
+        fn from_nanos(nanos: u64) -> Self;
 
-        fn get(self) -> Self::PrimitiveInner;
+        /// `None` if the duration exceeds `u64::MAX` nanoseconds
+        fn from_duration(duration: Duration) -> Option<Self>;
 
-        fn non_zero(self) -> Option<Self::NonZeroInner>;
-    }
+        fn nanos(self) -> u64;
 
-    pub trait New: Sized + Newtype {
-        fn new<T>(t: T) -> Self
-        where
-            Self::PrimitiveInner: From<T>;
+        fn seconds(self) -> u64;
+
+        fn duration(self) -> Duration;
+
+        /// checked, `None` if the sum exceeds `u64::MAX` nanoseconds or is rejected
+        fn plus(self, other: Self) -> Option<Self>;
     }
 
     #[macro_export]
-    macro_rules! uint_newtype_derive_attrs {
-        ($Item:ident, custom(uint_newtype(new))) => {
-            impl $crate::uint::New for $Item {
-                fn new<T>(t: T) -> Self
-                where
-                    Self::PrimitiveInner: From<T>,
-                {
-                    Self(Self::PrimitiveInner::from(t))
-                }
-            }
-        };
+    macro_rules! time_newtype_derive_attrs {
         ($_Item:ident, $($_other_meta:tt)+) => {};
     }
 
+    /// for `u64` nanosecond timestamps, stored like a uint newtype
+    ///
+    /// ```
+    /// use std::time::Duration;
+    ///
+    /// use macro_rules_attribute::derive;
+    /// use newtype_macros::prelude::*;
+    /// use newtype_macros::{ItemStoreImpl, TimestampNewtypeImpl};
+    ///
+    /// #[derive(Debug, Clone, Copy, PartialEq, TimestampNewtypeImpl!, ItemStoreImpl!)]
+    /// struct Expiry(u64);
+    ///
+    /// let mut storage = MemoryStorage::default();
+    ///
+    /// let expiry = Expiry::from_duration(Duration::from_secs(90)).unwrap();
+    ///
+    /// expiry.plus(Expiry::from_nanos(500)).unwrap().save(&mut storage);
+    ///
+    /// assert_eq!(Expiry::load(&storage).unwrap().seconds(), 90);
+    /// ```
     #[macro_export]
-    macro_rules! UintNewtypeImpl {
+    macro_rules! TimestampNewtypeImpl {
         (
         $(#[$($meta_item:tt)+])*
-        $pub:vis struct $Newtype:ident($Uint:ty);
+        $pub:vis struct $Newtype:ident($field_vis:vis u64);
     ) => {
-            impl $Newtype {
-                fn from_owned_bytes(bytes: Vec<u8>) -> Self {
-                    let be_bytes =
-                        TryFrom::try_from(bytes).expect("always stored correct amount of bytes");
+            $crate::UintNewtypeImpl! {
+                $(#[$($meta_item)+])*
+                $pub struct $Newtype($field_vis u64);
+            }
 
-                    let primative = <Self as $crate::uint::Newtype>::PrimitiveInner::from_be_bytes(be_bytes);
+            impl $crate::time::Newtype for $Newtype {
+                fn from_nanos(nanos: u64) -> Self {
+                    Self(nanos).assert_valid()
+                }
 
-                    Self(primative)
+                fn from_duration(duration: std::time::Duration) -> Option<Self> {
+                    u64::try_from(duration.as_nanos())
+                        .ok()
+                        .map(Self)
+                        .filter(|value| value.check_valid().is_ok())
                 }
 
-                fn to_owned_bytes(&self) -> Vec<u8> {
-                    self.0.to_be_bytes().to_vec()
+                fn nanos(self) -> u64 {
+                    self.0
                 }
-            }
 
-            impl $crate::uint::Newtype for $Newtype {
-                type PrimitiveInner = $Uint;
-                type NonZeroInner = <$Uint as $crate::NonZeroEquivalent>::NonZeroEquivalent;
+                fn seconds(self) -> u64 {
+                    self.0 / 1_000_000_000
+                }
 
-                fn get(self) -> Self::PrimitiveInner {
-                    self.0
+                fn duration(self) -> std::time::Duration {
+                    std::time::Duration::from_nanos(self.0)
                 }
 
-                fn non_zero(self) -> Option<Self::NonZeroInner> {
-                    Self::NonZeroInner::new(self.0)
+                fn plus(self, other: Self) -> Option<Self> {
+                    self.0
+                        .checked_add(other.0)
+                        .map(Self)
+                        .filter(|value| value.check_valid().is_ok())
                 }
             }
 
             $(
-                $crate::uint_newtype_derive_attrs!($Newtype, $($meta_item)+);
+                $crate::time_newtype_derive_attrs!($Newtype, $($meta_item)+);
             )*
         };
     }
 }
 
-pub mod string {
+pub mod fixed_point {
     pub trait Newtype: Sized {
-        fn new<S>(s: S) -> Self
-        where
-            S: Into<String>;
+        /// number of implied decimal places
+        const SCALE: u32;
 
-        fn as_str(&self) -> &str;
+        /// panics on a value rejected by `custom(uint_newtype(validate))`
+        fn from_atomics(atomics: u128) -> Self;
 
-        fn into_string(self) -> String;
+        fn atomics(&self) -> u128;
+
+        /// lossy above 2^53 atomics
+        fn to_f64(&self) -> f64 {
+            self.atomics() as f64 / 10f64.powi(Self::SCALE as i32)
+        }
     }
 
-    pub trait New: Sized {
-        fn new(s: String) -> Self;
+    /// `atomics` with the decimal point `scale` places from the right, trailing zeros trimmed
+    pub fn fmt(atomics: u128, scale: u32, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let unit = 10u128.pow(scale);
+        let (whole, fraction) = (atomics / unit, atomics % unit);
+
+        if fraction == 0 {
+            return write!(f, "{whole}");
+        }
+
+        let fraction = format!("{fraction:0width$}", width = scale as usize);
+
+        write!(f, "{whole}.{}", fraction.trim_end_matches('0'))
     }
 
+    /// the scale from `custom(fixed_point(scale = N))`, which is required
     #[macro_export]
-    macro_rules! string_newtype_derive_attrs {
+    macro_rules! fixed_point_scale {
+        () => {
+            compile_error!("`FixedPointNewtypeImpl!` requires a `#[custom(fixed_point(scale = N))]` attribute")
+        };
+        ([custom(fixed_point(scale = $scale:literal))] $($_rest:tt)*) => {{
+            assert!($scale <= 38, "`fixed_point(scale = ...)` is at most 38 for a `u128`");
+            $scale
+        }};
+        ([$($_other_meta:tt)+] $($rest:tt)*) => {
+            $crate::fixed_point_scale!($($rest)*)
+        };
+    }
+
+    #[macro_export]
+    macro_rules! fixed_point_derive_attrs {
+        ($_Item:ident, custom(fixed_point(scale = $_scale:literal))) => {};
+        ($_Item:ident, custom(fixed_point($($unknown:tt)*))) => {
+            compile_error!(concat!("unknown `fixed_point` attribute: `", stringify!($($unknown)*), "`"));
+        };
         ($_Item:ident, $($_other_meta:tt)+) => {};
     }
 
+    /// for `u128` atomics with an implied `custom(fixed_point(scale = N))`, stored like a uint newtype
+    ///
+    /// ```
+    /// use macro_rules_attribute::derive;
+    /// use newtype_macros::prelude::*;
+    /// use newtype_macros::{FixedPointNewtypeImpl, ItemStoreImpl};
+    ///
+    /// #[derive(Debug, PartialEq, FixedPointNewtypeImpl!, ItemStoreImpl!)]
+    /// #[custom(fixed_point(scale = 6))]
+    /// struct Price(u128);
+    ///
+    /// let mut storage = MemoryStorage::default();
+    ///
+    /// Price::from_atomics(1_500_000).save(&mut storage);
+    ///
+    /// assert_eq!(Price::load(&storage).unwrap().to_string(), "1.5");
+    /// ```
     #[macro_export]
-    macro_rules! StringNewtypeImpl {
+    macro_rules! FixedPointNewtypeImpl {
         (
         $(#[$($meta_item:tt)+])*
-        $pub:vis struct $Newtype:ident(String);
+        $pub:vis struct $Newtype:ident($field_vis:vis u128);
     ) => {
-            impl $Newtype {
-                fn from_owned_bytes(bytes: Vec<u8>) -> Self {
-                    String::from_utf8(bytes)
-                        .ok()
-                        .map(Self)
-                        .expect("stored valid utf-8")
-                }
-
-                fn to_owned_bytes(&self) -> Vec<u8> {
-                    self.0.as_bytes().to_owned()
-                }
+            $crate::UintNewtypeImpl! {
+                $(#[$($meta_item)+])*
+                $pub struct $Newtype($field_vis u128);
             }
 
-            impl $crate::string::Newtype for $Newtype {
-                fn new<S>(s: S) -> Self
-                where
-                    S: Into<String> {
-                    Self(s.into())
-                }
+            impl $crate::fixed_point::Newtype for $Newtype {
+                const SCALE: u32 = $crate::fixed_point_scale!($([$($meta_item)+])*);
 
-                fn as_str(&self) -> &str {
-                    self.0.as_str()
+                fn from_atomics(atomics: u128) -> Self {
+                    Self(atomics).assert_valid()
                 }
 
-                fn into_string(self) -> String {
+                fn atomics(&self) -> u128 {
                     self.0
                 }
             }
 
+            impl std::fmt::Display for $Newtype {
+                fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+                    $crate::fixed_point::fmt(self.0, <Self as $crate::fixed_point::Newtype>::SCALE, f)
+                }
+            }
+
             $(
-                $crate::string_newtype_derive_attrs!($Newtype, $($meta_item)+);
+                $crate::fixed_point_derive_attrs!($Newtype, $($meta_item)+);
             )*
         };
     }
 }
 
+#[cfg(feature = "testing")]
+pub mod testing {
+    use std::collections::{BTreeMap, HashSet};
+    use std::ops::Bound;
+
+    use crate::{IterableStorage, MutableStorage, ReadonlyStorage};
+
+    /// in-memory storage for tests, keys are kept in ascending order
+    #[derive(Debug, Default, Clone, PartialEq)]
+    pub struct MemoryStorage(BTreeMap<Vec<u8>, Vec<u8>>);
+
+    impl MemoryStorage {
+        pub fn len(&self) -> usize {
+            self.0.len()
+        }
+
+        pub fn is_empty(&self) -> bool {
+            self.0.is_empty()
+        }
+
+        pub fn snapshot(&self) -> Snapshot {
+            Snapshot::of(self)
+        }
+
+        /// all stored keys, lossily decoded as utf-8
+        pub fn keys(&self) -> Vec<String> {
+            self.0
+                .keys()
+                .map(|k| String::from_utf8_lossy(k).into_owned())
+                .collect()
+        }
+    }
+
+    /// the contents of a `MemoryStorage` at one point, `diff` two to see which keys changed
+    #[derive(Debug, Clone, PartialEq)]
+    pub struct Snapshot(BTreeMap<Vec<u8>, Vec<u8>>);
+
+    /// keys lossily decoded as utf-8, each list in ascending key order
+    #[derive(Debug, Default, Clone, PartialEq, Eq)]
+    pub struct StorageDiff {
+        pub added: Vec<String>,
+        pub removed: Vec<String>,
+        pub changed: Vec<String>,
+    }
+
+    impl StorageDiff {
+        pub fn is_empty(&self) -> bool {
+            self.added.is_empty() && self.removed.is_empty() && self.changed.is_empty()
+        }
+    }
+
+    impl Snapshot {
+        pub fn of(storage: &MemoryStorage) -> Self {
+            Self(storage.0.clone())
+        }
+
+        /// what changed from `self` to `other`
+        pub fn diff(&self, other: &Snapshot) -> StorageDiff {
+            let decode = |key: &Vec<u8>| String::from_utf8_lossy(key).into_owned();
+
+            let mut diff = StorageDiff::default();
+
+            for (key, value) in &self.0 {
+                match other.0.get(key) {
+                    None => diff.removed.push(decode(key)),
+                    Some(other_value) if other_value != value => diff.changed.push(decode(key)),
+                    Some(_) => {}
+                }
+            }
+
+            diff.added = other
+                .0
+                .keys()
+                .filter(|key| !self.0.contains_key(*key))
+                .map(decode)
+                .collect();
+
+            diff
+        }
+    }
+
+    /// panics if any of the item `KEY`s / map `KEY_PREFIX`s are equal, e.g. `FooUint(u64)` & `Foo(UintU64)`
+    pub fn assert_no_collisions(keys: &[&str]) {
+        let mut seen = HashSet::new();
+
+        for key in keys {
+            assert!(seen.insert(key), "storage key collision: {key}");
+        }
+    }
+
+    impl ReadonlyStorage for MemoryStorage {
+        fn get(&self, key: &[u8]) -> Option<Vec<u8>> {
+            self.0.get(key).cloned()
+        }
+    }
+
+    impl MutableStorage for MemoryStorage {
+        fn set(&mut self, key: &[u8], value: &[u8]) {
+            self.0.insert(key.to_owned(), value.to_owned());
+        }
+
+        fn clear(&mut self, key: &[u8]) {
+            self.0.remove(key);
+        }
+    }
+
+    impl IterableStorage for MemoryStorage {
+        fn prefix_iter(&self, prefix: &[u8]) -> Box<dyn Iterator<Item = (Vec<u8>, Vec<u8>)> + '_> {
+            let prefix = prefix.to_owned();
+
+            Box::new(
+                self.0
+                    .range(prefix.clone()..)
+                    .take_while(move |(k, _)| k.starts_with(&prefix))
+                    .map(|(k, v)| (k.clone(), v.clone())),
+            )
+        }
+
+        fn prefix_iter_rev(
+            &self,
+            prefix: &[u8],
+        ) -> Box<dyn Iterator<Item = (Vec<u8>, Vec<u8>)> + '_> {
+            let end = prefix_end(prefix).map_or(Bound::Unbounded, Bound::Excluded);
+
+            Box::new(
+                self.0
+                    .range((Bound::Included(prefix.to_owned()), end))
+                    .rev()
+                    .map(|(k, v)| (k.clone(), v.clone())),
+            )
+        }
+    }
+
+    /// the smallest key after every key starting with `prefix`, `None` if there is none
+    fn prefix_end(prefix: &[u8]) -> Option<Vec<u8>> {
+        let mut end = prefix.to_owned();
+
+        while let Some(last) = end.pop() {
+            if last < u8::MAX {
+                end.push(last + 1);
+                return Some(end);
+            }
+        }
+
+        None
+    }
+}
+
+/// every trait of the crate, or a single kind's through its submodule, e.g. `prelude::uint::*`
 pub mod prelude {
-    pub use crate::item::{Clear, LoadAlways as ItemLoadAlways, Store as ItemStore};
-    pub use crate::map::{ClearAt, LoadAlwaysAt, Store as MapStore};
-    pub use crate::non_zero::{CheckedNew, FromNonZero, Newtype as NonZeroNewtype};
-    pub use crate::string::{New as NewStringNewtype, Newtype as StringNewtype};
-    pub use crate::uint::{New as NewUintNewtype, Newtype as UintNewtype};
+    /// the storage traits, and `MemoryStorage` with the `testing` feature
+    ///
+    /// ```
+    /// use newtype_macros::prelude::storage::*;
+    ///
+    /// let mut storage = MemoryStorage::default();
+    ///
+    /// storage.set(b"k", b"v");
+    ///
+    /// assert_eq!(storage.get(b"k"), Some(b"v".to_vec()));
+    /// ```
+    pub mod storage {
+        #[cfg(feature = "testing")]
+        pub use crate::testing::MemoryStorage;
+        pub use crate::{
+            IterableMutableStorage, IterableStorage, MutableStorage, ReadWriteStorage,
+            ReadonlyStorage, StorageExt, TryMutableStorage,
+        };
+    }
+
+    /// the `ItemStoreImpl!` traits
+    ///
+    /// ```
+    /// use macro_rules_attribute::derive;
+    /// use newtype_macros::prelude::item::*;
+    /// use newtype_macros::prelude::storage::MemoryStorage;
+    /// use newtype_macros::{ItemStoreImpl, UintNewtypeImpl};
+    ///
+    /// #[derive(Debug, PartialEq, UintNewtypeImpl!, ItemStoreImpl!)]
+    /// #[custom(item_store(clear))]
+    /// struct Height(u64);
+    ///
+    /// let mut storage = MemoryStorage::default();
+    ///
+    /// Height(1).save(&mut storage);
+    /// Height::clear(&mut storage);
+    ///
+    /// assert_eq!(Height::load(&storage), None);
+    /// ```
+    pub mod item {
+        pub use crate::item::{
+            Clear, DynStore, KeepPrevious, Key as ItemKey, LoadAlways as ItemLoadAlways,
+            LoadOrDefault, Store as ItemStore,
+        };
+    }
+
+    /// the `MapStoreImpl!` traits
+    ///
+    /// ```
+    /// use macro_rules_attribute::derive;
+    /// use newtype_macros::prelude::map::*;
+    /// use newtype_macros::prelude::storage::MemoryStorage;
+    /// use newtype_macros::{MapStoreImpl, UintNewtypeImpl};
+    ///
+    /// #[derive(Debug, PartialEq, UintNewtypeImpl!, MapStoreImpl!)]
+    /// #[custom(map_store(key = String, counter))]
+    /// struct Votes(u32);
+    ///
+    /// let mut storage = MemoryStorage::default();
+    ///
+    /// assert_eq!(Votes::increment_at(&mut storage, "yes".to_owned()), Ok(1));
+    /// assert_eq!(Votes::load_at(&storage, "yes".to_owned()), Some(Votes(1)));
+    /// ```
+    pub mod map {
+        pub use crate::map::{
+            ClearAt, Counter, DoubleMap, IndexedStore, LoadAlwaysAt, Prefix as MapPrefix,
+            SelfKeyed, Store as MapStore,
+        };
+    }
+
+    /// the `UintNewtypeImpl!` traits
+    ///
+    /// ```
+    /// use macro_rules_attribute::derive;
+    /// use newtype_macros::prelude::uint::*;
+    /// use newtype_macros::UintNewtypeImpl;
+    ///
+    /// #[derive(Debug, PartialEq, UintNewtypeImpl!)]
+    /// #[custom(uint_newtype(new))]
+    /// #[custom(uint_newtype(arithmetic))]
+    /// struct Supply(u128);
+    ///
+    /// assert_eq!(Supply::new(5u8).checked_add(Supply(2)), Some(Supply(7)));
+    /// assert_eq!(Supply(7).get(), 7);
+    /// ```
+    pub mod uint {
+        pub use crate::uint::{
+            Arithmetic as UintArithmetic, New as NewUintNewtype, Newtype as UintNewtype,
+            TryNew as TryNewUintNewtype,
+        };
+    }
+
+    /// the `NonZeroNewtypeImpl!` traits
+    ///
+    /// ```
+    /// use std::num::NonZeroU32;
+    ///
+    /// use macro_rules_attribute::derive;
+    /// use newtype_macros::prelude::non_zero::*;
+    /// use newtype_macros::NonZeroNewtypeImpl;
+    ///
+    /// #[derive(Debug, PartialEq, NonZeroNewtypeImpl!)]
+    /// #[custom(non_zero_newtype(checked_new))]
+    /// struct Epoch(NonZeroU32);
+    ///
+    /// assert_eq!(Epoch::checked_new(0u32), None);
+    /// assert_eq!(Epoch::checked_new(3u32).unwrap().get(), 3);
+    /// ```
+    pub mod non_zero {
+        pub use crate::non_zero::{
+            Arithmetic as NonZeroArithmetic, CheckedNew, FromNonZero, Newtype as NonZeroNewtype,
+            TryNew as TryNewNonZeroNewtype,
+        };
+    }
+
+    /// the `StringNewtypeImpl!` traits
+    ///
+    /// ```
+    /// use macro_rules_attribute::derive;
+    /// use newtype_macros::prelude::string::*;
+    /// use newtype_macros::StringNewtypeImpl;
+    ///
+    /// #[derive(Debug, PartialEq, StringNewtypeImpl!)]
+    /// #[custom(string_newtype(len(max = 8)))]
+    /// struct Ticker(String);
+    ///
+    /// assert_eq!(Ticker::new("atom").as_str(), "atom");
+    /// assert!(Ticker::try_new("much too long").is_err());
+    /// ```
+    pub mod string {
+        pub use crate::string::{
+            New as NewStringNewtype, Newtype as StringNewtype, TryNew as TryNewStringNewtype,
+        };
+    }
+
+    pub use self::item::*;
+    pub use self::map::*;
+    pub use self::non_zero::*;
+    pub use self::storage::*;
+    pub use self::string::*;
+    pub use self::uint::*;
+    pub use crate::address::Newtype as AddressNewtype;
+    pub use crate::array::Newtype as ArrayNewtype;
+    pub use crate::enumeration::Newtype as EnumNewtype;
+    pub use crate::fixed_point::Newtype as FixedPointNewtype;
+    pub use crate::map_field::Newtype as MapFieldNewtype;
+    pub use crate::option::Newtype as OptionNewtype;
+    pub use crate::time::Newtype as TimeNewtype;
+    #[cfg(feature = "bignum")]
+    pub use crate::u256::Newtype as U256Newtype;
+    pub use crate::vec::Newtype as VecNewtype;
+    pub use crate::Validate;
 }
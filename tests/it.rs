@@ -1,10 +1,24 @@
-use std::num::NonZeroU128;
+use std::collections::{BTreeMap, HashMap};
+use std::num::{NonZeroI64, NonZeroU128, NonZeroU16, NonZeroU8};
+use std::sync::Arc;
+use std::time::Duration;
 
 use expect_test::{expect, Expect};
 use macro_rules_attribute::derive;
 
-use newtype_macros::{prelude::*, MapKeyImpl, MapStoreImpl, NonZeroNewtypeImpl, StringNewtypeImpl};
-use newtype_macros::{ItemStoreImpl, MutableStorage, ReadonlyStorage, UintNewtypeImpl};
+use newtype_macros::map::{FromMapKey, IntoMapKey, ToMapKey};
+use newtype_macros::string::StringError;
+use newtype_macros::testing::{assert_no_collisions, StorageDiff};
+use newtype_macros::u256::U256;
+use newtype_macros::{
+    prelude::*, AddressNewtypeImpl, ArrayNewtypeImpl, EnumNewtypeImpl, FixedPointNewtypeImpl,
+    MapFieldNewtypeImpl, MapKeyImpl, MapStoreImpl, NonZeroNewtypeImpl, OptionNewtypeImpl,
+    StringNewtypeImpl, TimestampNewtypeImpl, U256NewtypeImpl, VecNewtypeImpl,
+};
+use newtype_macros::{
+    BufferedStorage, ItemStoreImpl, LoadError, NamespacedStorage, ReadonlyNamespacedStorage,
+    StorageError, TryMutableStorage, UintNewtypeImpl, ValidationError,
+};
 
 pub fn check(actual: impl std::fmt::Debug, expected: Expect) {
     expected.assert_eq(&format!("{actual:#?}"));
@@ -157,3 +171,3183 @@ fn string_map_storage() {
 
     assert_eq!(x.as_str(), "world");
 }
+
+#[derive(Debug, PartialEq, StringNewtypeImpl!, MapStoreImpl!)]
+#[custom(map_store(key, u32))]
+#[custom(map_store(index(owner, String)))]
+struct AccountOwner(String);
+
+impl AccountOwner {
+    fn owner(&self) -> String {
+        self.0.clone()
+    }
+}
+
+#[test]
+fn indexed_map_storage() {
+    let mut storage = MemoryStorage::default();
+
+    AccountOwner::new("alice").save_indexed_at(&mut storage, 1);
+    AccountOwner::new("bob").save_indexed_at(&mut storage, 2);
+    AccountOwner::new("alice").save_indexed_at(&mut storage, 3);
+
+    check(
+        AccountOwner::load_by_index(&storage, "alice".to_owned()),
+        expect![[r#"
+            [
+                1,
+                3,
+            ]"#]],
+    );
+
+    assert_eq!(
+        AccountOwner::load_at(&storage, 3).unwrap(),
+        AccountOwner::new("alice")
+    );
+
+    AccountOwner::new("alice").clear_indexed_at(&mut storage, 1);
+
+    assert!(AccountOwner::load_at(&storage, 1).is_none());
+
+    assert_eq!(
+        AccountOwner::load_by_index(&storage, "alice".to_owned()),
+        [3]
+    );
+}
+
+#[test]
+fn index_follows_a_changed_secondary() {
+    let mut storage = MemoryStorage::default();
+
+    AccountOwner::new("alice").save_indexed_at(&mut storage, 1);
+    AccountOwner::new("bob").save_indexed_at(&mut storage, 1);
+
+    assert!(AccountOwner::load_by_index(&storage, "alice".to_owned()).is_empty());
+    assert_eq!(AccountOwner::load_by_index(&storage, "bob".to_owned()), [1]);
+
+    AccountOwner::new("alice").clear_indexed_at(&mut storage, 1);
+
+    assert!(storage.is_empty());
+}
+
+#[derive(Debug, PartialEq, StringNewtypeImpl!, MapStoreImpl!)]
+#[custom(map_store(key, u32))]
+#[custom(map_store(index(owner, String)))]
+#[custom(map_store(clear))]
+struct ClearableOwner(String);
+
+impl ClearableOwner {
+    fn owner(&self) -> String {
+        self.0.clone()
+    }
+}
+
+#[test]
+fn plain_writes_keep_the_index_in_sync() {
+    let mut storage = MemoryStorage::default();
+
+    ClearableOwner::new("alice").save_at(&mut storage, 1);
+    ClearableOwner::new("alice").save_at(&mut storage, 2);
+
+    assert_eq!(
+        ClearableOwner::load_by_index(&storage, "alice".to_owned()),
+        [1, 2]
+    );
+
+    ClearableOwner::new("bob").save_at(&mut storage, 2);
+
+    assert_eq!(
+        ClearableOwner::load_by_index(&storage, "alice".to_owned()),
+        [1]
+    );
+    assert_eq!(
+        ClearableOwner::load_by_index(&storage, "bob".to_owned()),
+        [2]
+    );
+
+    ClearableOwner::move_at(&mut storage, 1, 3);
+
+    assert_eq!(
+        ClearableOwner::load_by_index(&storage, "alice".to_owned()),
+        [3]
+    );
+}
+
+#[test]
+fn clearing_removes_the_index_entry() {
+    let mut storage = MemoryStorage::default();
+
+    ClearableOwner::new("alice").save_at(&mut storage, 1);
+    ClearableOwner::clear_at(&mut storage, 1);
+
+    assert!(ClearableOwner::load_by_index(&storage, "alice".to_owned()).is_empty());
+
+    ClearableOwner::new("alice").save_at(&mut storage, 1);
+    ClearableOwner::new("alice").clear_indexed_at(&mut storage, 1);
+
+    assert!(storage.is_empty());
+}
+
+#[test]
+fn index_secondaries_dont_prefix_each_other() {
+    let mut storage = MemoryStorage::default();
+
+    AccountOwner::new("a").save_indexed_at(&mut storage, 1);
+    AccountOwner::new("a::b").save_indexed_at(&mut storage, 2);
+
+    assert_eq!(AccountOwner::load_by_index(&storage, "a".to_owned()), [1]);
+    assert_eq!(
+        AccountOwner::load_by_index(&storage, "a::b".to_owned()),
+        [2]
+    );
+}
+
+#[derive(Debug, PartialEq, StringNewtypeImpl!, MapStoreImpl!)]
+#[custom(map_store(key, (String, u32)))]
+#[custom(map_store(escape_keys))]
+#[custom(map_store(clear))]
+#[custom(map_store(index(owner, String)))]
+struct Holding(String);
+
+impl Holding {
+    fn owner(&self) -> String {
+        self.0.clone()
+    }
+}
+
+#[test]
+fn index_on_an_escaped_map() {
+    let mut storage = MemoryStorage::default();
+
+    let key = ("x:y".to_owned(), 1u32);
+
+    Holding::new("alice").save_indexed_at(&mut storage, key.clone());
+
+    assert_eq!(
+        Holding::load_at(&storage, &key),
+        Some(Holding::new("alice"))
+    );
+    assert_eq!(Holding::load_by_index(&storage, "alice".to_owned()), [key]);
+
+    Holding::new("bob").save_indexed_at(&mut storage, ("z".to_owned(), 2));
+
+    Holding::clear_all(&mut storage);
+
+    assert!(storage.is_empty());
+}
+
+#[derive(Debug, PartialEq, StringNewtypeImpl!, MapStoreImpl!)]
+#[custom(string_newtype(intern))]
+#[custom(map_store(key, u32))]
+struct Denom(Arc<str>);
+
+#[test]
+fn interned_string_newtype() {
+    let a = Denom::new("x");
+    let b = Denom::new("x");
+
+    assert!(std::ptr::eq(a.as_str(), b.as_str()));
+
+    assert!(!std::ptr::eq(a.as_str(), Denom::new("y").as_str()));
+
+    let mut storage = MemoryStorage::default();
+
+    a.save_at(&mut storage, 1);
+
+    let loaded = Denom::load_at(&storage, 1).unwrap();
+
+    assert!(std::ptr::eq(loaded.as_str(), b.as_str()));
+
+    assert_eq!(loaded.into_string(), "x");
+}
+
+#[test]
+fn interned_strings_are_dropped_once_unused() {
+    let held = Denom::new("held");
+    let unused = Arc::downgrade(&Denom::new("unused").0);
+
+    for i in 0..1_000 {
+        let _ = Denom::new(format!("filler-{i}"));
+    }
+
+    assert!(unused.upgrade().is_none());
+    assert!(std::ptr::eq(held.as_str(), Denom::new("held").as_str()));
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, TimestampNewtypeImpl!, ItemStoreImpl!)]
+#[custom(item_store(always))]
+struct Expiry(u64);
+
+#[test]
+fn timestamp_item_storage() {
+    let mut storage = SingleCellStore::default();
+
+    let x = Expiry::from_duration(Duration::from_secs(90)).unwrap();
+
+    x.save(&mut storage);
+
+    check(
+        storage.key_str(),
+        expect![[r#"
+            Some(
+                "it::expiry_u64",
+            )"#]],
+    );
+
+    let x = Expiry::load_always(&storage);
+
+    assert_eq!(x.seconds(), 90);
+
+    let x = x.plus(Expiry::from_nanos(30_500_000_000)).unwrap();
+
+    assert_eq!(x.seconds(), 120);
+    assert_eq!(x.duration(), Duration::from_millis(120_500));
+
+    assert_eq!(
+        Expiry::from_nanos(u64::MAX).plus(Expiry::from_nanos(1)),
+        None
+    );
+    assert_eq!(Expiry::from_duration(Duration::MAX), None);
+}
+
+#[derive(Debug, PartialEq, UintNewtypeImpl!, MapStoreImpl!)]
+#[custom(uint_newtype(new))]
+#[custom(map_store(key, String))]
+struct Balance(u32);
+
+#[test]
+fn migrate_map_values() {
+    let mut storage = MemoryStorage::default();
+
+    Balance::new(1u32).save_at(&mut storage, "a".to_owned());
+    Balance::new(258u32).save_at(&mut storage, "b".to_owned());
+
+    Balance::migrate_values(&mut storage, |mut bytes| {
+        bytes.reverse();
+        bytes
+    });
+
+    let load_le = |key: &str| {
+        let bytes = storage.get(format!("it::balance_u32::{key}").as_bytes())?;
+        Some(u32::from_le_bytes(bytes.try_into().unwrap()))
+    };
+
+    assert_eq!(load_le("a"), Some(1));
+    assert_eq!(load_le("b"), Some(258));
+}
+
+#[derive(Debug, PartialEq, UintNewtypeImpl!)]
+#[custom(uint_newtype(new))]
+#[custom(uint_newtype(arithmetic))]
+struct Small(u8);
+
+#[derive(Debug, PartialEq, NonZeroNewtypeImpl!)]
+#[custom(non_zero_newtype(checked_new))]
+#[custom(non_zero_newtype(arithmetic))]
+struct SmallNonZero(NonZeroU8);
+
+#[test]
+fn checked_arithmetic() {
+    assert_eq!(Small::new(1u8).checked_add(Small::new(2u8)), Some(Small(3)));
+    assert_eq!(Small::new(255u8).checked_add(Small::new(1u8)), None);
+    assert_eq!(Small::new(1u8).checked_sub(Small::new(2u8)), None);
+    assert_eq!(
+        Small::new(255u8).saturating_add(Small::new(1u8)),
+        Small(255)
+    );
+    assert_eq!(Small::new(1u8).saturating_sub(Small::new(2u8)), Small(0));
+
+    let nz = |n: u8| SmallNonZero::checked_new(n).unwrap();
+
+    assert_eq!(nz(1).checked_add(nz(2)), Some(nz(3)));
+    assert_eq!(nz(255).checked_add(nz(1)), None);
+    assert_eq!(nz(3).checked_sub(nz(1)), Some(nz(2)));
+    assert_eq!(nz(2).checked_sub(nz(2)), None);
+    assert_eq!(nz(255).saturating_add(nz(1)), nz(255));
+}
+
+#[test]
+fn memory_storage() {
+    let mut storage = MemoryStorage::default();
+
+    assert!(storage.is_empty());
+
+    FooUint::new(1u8).save(&mut storage);
+    FooString::new("x").save_at(&mut storage, "b".to_owned());
+    FooString::new("y").save_at(&mut storage, "a".to_owned());
+
+    assert_eq!(storage.len(), 3);
+
+    check(
+        storage.keys(),
+        expect![[r#"
+            [
+                "it::foo_string_string::a",
+                "it::foo_string_string::b",
+                "it::foo_uint_u64",
+            ]"#]],
+    );
+
+    assert_eq!(FooUint::load_always(&storage), FooUint(1));
+    assert_eq!(
+        FooString::load_always_at(&storage, "a".to_owned()).as_str(),
+        "y"
+    );
+}
+
+#[derive(Debug, PartialEq, Eq, UintNewtypeImpl!)]
+#[custom(uint_newtype(ord))]
+#[custom(uint_newtype(hash))]
+#[custom(uint_newtype(copy))]
+struct Rank(u64);
+
+#[derive(Debug, PartialEq, Eq, StringNewtypeImpl!)]
+#[custom(string_newtype(hash))]
+struct Tag(String);
+
+#[derive(Debug, NonZeroNewtypeImpl!)]
+#[custom(non_zero_newtype(checked_new))]
+#[custom(non_zero_newtype(hash))]
+#[custom(non_zero_newtype(eq))]
+#[custom(non_zero_newtype(ord))]
+struct Priority(NonZeroU8);
+
+#[test]
+fn ordered_numeric_newtypes() {
+    let mut xs = vec![Rank(3), Rank(1)];
+
+    xs.sort();
+
+    assert_eq!(xs, [Rank(1), Rank(3)]);
+
+    let set: std::collections::BTreeSet<_> = [Rank(2), Rank(1), Rank(2)].into();
+
+    assert_eq!(set.into_iter().collect::<Vec<_>>(), [Rank(1), Rank(2)]);
+
+    let p = |n: u8| Priority::checked_new(n).unwrap();
+
+    assert!(p(1) < p(2));
+    assert_eq!(p(5).max(p(4)), p(5));
+}
+
+#[test]
+fn migrate_legacy_keys() {
+    let mut storage = MemoryStorage::default();
+
+    storage.set(b"legacy::foo_uint_u64", &7u64.to_be_bytes());
+
+    FooUint::migrate_key(&mut storage, b"legacy::foo_uint_u64");
+
+    assert_eq!(FooUint::load_always(&storage), FooUint(7));
+
+    check(
+        storage.keys(),
+        expect![[r#"
+            [
+                "it::foo_uint_u64",
+            ]"#]],
+    );
+
+    storage.set(b"legacy::foo_string_string::a", b"hello");
+
+    FooString::migrate_key_at(
+        &mut storage,
+        b"legacy::foo_string_string::a",
+        "b".to_owned(),
+    );
+
+    assert_eq!(
+        FooString::load_always_at(&storage, "b".to_owned()).as_str(),
+        "hello"
+    );
+
+    assert_eq!(storage.len(), 2);
+}
+
+#[derive(Debug, PartialEq, EnumNewtypeImpl!, ItemStoreImpl!, MapStoreImpl!)]
+#[custom(map_store(key, Status))]
+#[repr(u8)]
+enum Status {
+    Pending,
+    Active = 4,
+    Closed,
+}
+
+#[test]
+fn enum_storage() {
+    let mut storage = MemoryStorage::default();
+
+    Status::Active.save(&mut storage);
+    Status::Closed.save_at(&mut storage, Status::Pending);
+
+    check(
+        storage.keys(),
+        expect![[r#"
+            [
+                "it::status",
+                "it::status::0",
+            ]"#]],
+    );
+
+    assert_eq!(storage.get(b"it::status"), Some(vec![4]));
+    assert_eq!(Status::load(&storage), Some(Status::Active));
+    assert_eq!(
+        Status::load_at(&storage, Status::Pending),
+        Some(Status::Closed)
+    );
+    assert_eq!(Status::from_discriminant(5), Some(Status::Closed));
+    assert_eq!(Status::from_discriminant(1), None);
+}
+
+#[test]
+#[should_panic(expected = "stored valid value: InvalidDiscriminant(1)")]
+fn enum_invalid_discriminant() {
+    let mut storage = MemoryStorage::default();
+
+    storage.set(b"it::status", &[1]);
+
+    let _ = Status::load(&storage);
+}
+
+#[test]
+fn iterate_map_storage() {
+    let mut storage = MemoryStorage::default();
+
+    BarString::new("a").save_at(&mut storage, (2, Baz::new(1u8)));
+    BarString::new("b").save_at(&mut storage, (1, Baz::new(7u8)));
+    BarString::new("c").save_at(&mut storage, (1, Baz::new(3u8)));
+    FooString::new("other").save_at(&mut storage, "x".to_owned());
+
+    assert_eq!(BarString::count(&storage), 3);
+
+    check(
+        BarString::iter_keys(&storage).collect::<Vec<_>>(),
+        expect![[r#"
+            [
+                (
+                    1,
+                    Baz(
+                        3,
+                    ),
+                ),
+                (
+                    1,
+                    Baz(
+                        7,
+                    ),
+                ),
+                (
+                    2,
+                    Baz(
+                        1,
+                    ),
+                ),
+            ]"#]],
+    );
+
+    check(
+        BarString::iter_values(&storage).collect::<Vec<_>>(),
+        expect![[r#"
+            [
+                BarString(
+                    "c",
+                ),
+                BarString(
+                    "b",
+                ),
+                BarString(
+                    "a",
+                ),
+            ]"#]],
+    );
+
+    assert_eq!(
+        FooString::iter(&storage).collect::<Vec<_>>(),
+        [("x".to_owned(), FooString::new("other"))]
+    );
+}
+
+#[derive(Debug, PartialEq, NonZeroNewtypeImpl!, MapKeyImpl!)]
+#[custom(non_zero_newtype(checked_new))]
+struct Slot(std::num::NonZeroU32);
+
+#[derive(Debug, PartialEq, StringNewtypeImpl!, MapStoreImpl!)]
+#[custom(map_store(key, (Slot, String)))]
+struct SlotLabel(String);
+
+#[test]
+fn non_zero_map_key() {
+    let mut storage = MemoryStorage::default();
+
+    SlotLabel::new("x").save_at(
+        &mut storage,
+        (Slot::checked_new(3u8).unwrap(), "a".to_owned()),
+    );
+
+    check(
+        storage.keys(),
+        expect![[r#"
+            [
+                "it::slot_label_string::3:a",
+            ]"#]],
+    );
+
+    check(
+        SlotLabel::iter_keys(&storage).collect::<Vec<_>>(),
+        expect![[r#"
+            [
+                (
+                    Slot(
+                        3,
+                    ),
+                    "a",
+                ),
+            ]"#]],
+    );
+}
+
+type UintU64 = u64;
+
+#[derive(Debug, PartialEq, UintNewtypeImpl!, ItemStoreImpl!)]
+struct Foo(UintU64);
+
+#[test]
+fn no_key_collisions() {
+    assert_no_collisions(&[
+        FooUint::KEY,
+        FooNonZero::KEY,
+        Expiry::KEY,
+        BarString::KEY_PREFIX,
+    ]);
+}
+
+#[test]
+#[should_panic(expected = "storage key collision: it::foo_uint_u64")]
+fn key_collision() {
+    assert_no_collisions(&[FooUint::KEY, Foo::KEY]);
+}
+
+/// doc comments are attributes too
+#[derive(Debug, UintNewtypeImpl!, ItemStoreImpl!)]
+#[cfg(test)]
+#[custom(uint_newtype(new))]
+#[allow(dead_code)]
+#[custom(item_store(clear))]
+#[derive(Clone, PartialEq)]
+#[non_exhaustive]
+pub struct MixedAttrs(pub u32);
+
+#[test]
+fn mixed_attributes() {
+    let mut storage = MemoryStorage::default();
+
+    let x = MixedAttrs::new(1u8);
+
+    x.clone().save(&mut storage);
+
+    assert_eq!(MixedAttrs::load(&storage), Some(x));
+
+    MixedAttrs::clear(&mut storage);
+
+    assert!(storage.is_empty());
+}
+
+#[derive(Debug, PartialEq, UintNewtypeImpl!, ItemStoreImpl!, MapStoreImpl!)]
+#[custom(item_store(key = "a"))]
+#[custom(map_store(prefix = "b"))]
+#[custom(map_store(key, u8))]
+struct ShortKey(u16);
+
+#[test]
+fn literal_storage_keys() {
+    let mut storage = MemoryStorage::default();
+
+    ShortKey(1).save(&mut storage);
+    ShortKey(2).save_at(&mut storage, 3);
+
+    check(
+        storage.keys(),
+        expect![[r#"
+            [
+                "a",
+                "b::3",
+            ]"#]],
+    );
+
+    assert_eq!(ShortKey::load(&storage), Some(ShortKey(1)));
+    assert_eq!(ShortKey::load_at(&storage, 3), Some(ShortKey(2)));
+}
+
+#[test]
+fn hashed_newtypes() {
+    let mut by_uint = std::collections::HashMap::new();
+
+    by_uint.insert(Rank(1), "one");
+    by_uint.insert(Rank(2), "two");
+
+    assert_eq!(by_uint.get(&Rank(2)), Some(&"two"));
+    assert_eq!(by_uint.get(&Rank(3)), None);
+
+    let strings: std::collections::HashSet<_> = [Tag::new("a"), Tag::new("a")].into();
+
+    assert_eq!(strings.len(), 1);
+
+    let priorities: std::collections::HashSet<_> = [1u8, 2, 1]
+        .into_iter()
+        .map(|n| Priority::checked_new(n).unwrap())
+        .collect();
+
+    assert_eq!(priorities.len(), 2);
+}
+
+#[derive(Debug, PartialEq, StringNewtypeImpl!, ItemStoreImpl!)]
+#[custom(string_newtype(len(min = 3, max = 8)))]
+struct Ticker(String);
+
+#[test]
+fn string_length_bounds() {
+    check(
+        Ticker::try_new("ab"),
+        expect![[r#"
+            Err(
+                Len(
+                    StringLenError {
+                        len: 2,
+                        min: 3,
+                        max: 8,
+                    },
+                ),
+            )"#]],
+    );
+
+    assert!(Ticker::try_new("abcdefghi").is_err());
+
+    assert_eq!(Ticker::try_new("atom"), Ok(Ticker::new("atom")));
+
+    let mut storage = MemoryStorage::default();
+
+    Ticker::try_new("atom").unwrap().save(&mut storage);
+
+    assert_eq!(Ticker::try_load(&storage), Ok(Some(Ticker::new("atom"))));
+
+    storage.set(b"it::ticker_string", b"ab");
+
+    check(
+        Ticker::try_load(&storage),
+        expect![[r#"
+            Err(
+                StringLen(
+                    StringLenError {
+                        len: 2,
+                        min: 3,
+                        max: 8,
+                    },
+                ),
+            )"#]],
+    );
+
+    storage.set(b"it::ticker_string", &[0xff, 0xfe, 0xfd]);
+
+    assert_eq!(Ticker::try_load(&storage), Err(LoadError::InvalidUtf8));
+}
+
+#[test]
+#[should_panic(expected = "string byte length 2 outside of 3..=8")]
+fn string_new_rejects_out_of_bounds_length() {
+    let _ = Ticker::new("ab");
+}
+
+#[derive(Debug, PartialEq, ArrayNewtypeImpl!, ItemStoreImpl!, MapStoreImpl!)]
+#[custom(map_store(key, Hash))]
+struct Hash([u8; 32]);
+
+#[test]
+fn array_storage() {
+    let mut storage = MemoryStorage::default();
+
+    Hash([0u8; 32]).save(&mut storage);
+
+    check(
+        storage.keys(),
+        expect![[r#"
+            [
+                "it::hash_u8_array",
+            ]"#]],
+    );
+
+    assert_eq!(storage.get(b"it::hash_u8_array"), Some(vec![0u8; 32]));
+
+    let x = Hash::load(&storage).unwrap();
+
+    assert_eq!(x.as_slice(), &[0u8; 32]);
+    assert_eq!(x.into_array(), [0u8; 32]);
+
+    let mut key = [0u8; 32];
+    key[0] = 0xab;
+    key[31] = 0x01;
+
+    Hash([7u8; 32]).save_at(&mut storage, Hash(key));
+
+    assert_eq!(Hash::iter_keys(&storage).collect::<Vec<_>>(), [Hash(key)]);
+
+    assert!(storage.keys().contains(
+        &"it::hash_u8_array::ab00000000000000000000000000000000000000000000000000000000000001"
+            .to_owned()
+    ));
+
+    storage.set(b"it::hash_u8_array", &[1, 2, 3]);
+
+    assert_eq!(
+        Hash::try_load(&storage),
+        Err(LoadError::ByteLength {
+            expected: 32,
+            found: 3
+        })
+    );
+}
+
+#[test]
+fn clear_all_map_entries() {
+    let mut storage = MemoryStorage::default();
+
+    for n in 0..5u32 {
+        BarString::new("x").save_at(&mut storage, (n, Baz::new(1u8)));
+    }
+
+    FooString::new("kept").save_at(&mut storage, "a".to_owned());
+
+    assert_eq!(BarString::count(&storage), 5);
+
+    BarString::clear_all(&mut storage);
+
+    assert_eq!(BarString::count(&storage), 0);
+    assert_eq!(storage.len(), 1);
+}
+
+#[test]
+fn borrowed_map_keys() {
+    let mut storage = MemoryStorage::default();
+
+    let key = "address".to_owned();
+
+    FooString::new("x").save_at_ref(&mut storage, &key);
+
+    for _ in 0..3 {
+        assert_eq!(
+            FooString::load_at_ref(&storage, &key),
+            Some(FooString::new("x"))
+        );
+    }
+
+    assert_eq!(FooString::load_at(&storage, key), Some(FooString::new("x")));
+}
+
+#[test]
+fn reused_map_keys() {
+    let mut storage = MemoryStorage::default();
+
+    let key = (7u32, Baz::new(2u8));
+
+    BarString::new("x").save_at(&mut storage, &key);
+
+    assert_eq!(
+        BarString::load_at(&storage, &key),
+        Some(BarString::new("x"))
+    );
+    assert_eq!(
+        BarString::try_load_at(&storage, &key),
+        Ok(Some(BarString::new("x")))
+    );
+
+    assert_eq!(key.to_map_key(), "7:2");
+    assert_eq!(key.into_map_key(), "7:2");
+}
+
+#[test]
+fn typed_storage_access() {
+    let mut storage = MemoryStorage::default();
+
+    storage.set_item(&FooUint(5));
+    storage.set_map(&(1u32, Baz::new(2u8)), &BarString::new("x"));
+
+    assert_eq!(storage.get_item::<FooUint>(), Some(FooUint(5)));
+    assert_eq!(
+        storage.get_map::<BarString>((1u32, Baz::new(2u8))),
+        Some(BarString::new("x"))
+    );
+
+    let mut handle: &mut dyn ReadWriteStorage = &mut storage;
+
+    handle.set_item(&FooUint(6));
+
+    let handle: &dyn ReadonlyStorage = &storage;
+
+    assert_eq!(handle.get_item::<FooUint>(), Some(FooUint(6)));
+    assert_eq!(handle.get_item::<Expiry>(), None);
+}
+
+#[derive(Debug, PartialEq, OptionNewtypeImpl!, ItemStoreImpl!)]
+struct MaybeFee(Option<u64>);
+
+#[test]
+fn optional_inner_storage() {
+    let mut storage = MemoryStorage::default();
+
+    assert!(!MaybeFee::exists(&storage));
+
+    MaybeFee(None).save(&mut storage);
+
+    assert!(MaybeFee::exists(&storage));
+    assert_eq!(storage.get(b"it::maybe_fee_option"), Some(vec![0]));
+    assert_eq!(MaybeFee::load(&storage).unwrap().get(), None);
+
+    MaybeFee(Some(3)).save(&mut storage);
+
+    assert_eq!(
+        storage.get(b"it::maybe_fee_option"),
+        Some(vec![1, 0, 0, 0, 0, 0, 0, 0, 3])
+    );
+    assert_eq!(MaybeFee::load(&storage).unwrap().get(), Some(3));
+
+    storage.set(b"it::maybe_fee_option", &[2]);
+
+    assert_eq!(
+        MaybeFee::try_load(&storage),
+        Err(LoadError::InvalidDiscriminant(2))
+    );
+}
+
+#[test]
+fn borrowed_str_map_keys() {
+    use std::borrow::Cow;
+
+    use newtype_macros::map::FromMapKey;
+
+    assert_eq!("a".into_map_key(), "a".to_string().into_map_key());
+    assert_eq!(
+        Cow::Borrowed("a").into_map_key(),
+        "a".to_string().into_map_key()
+    );
+    assert_eq!(
+        ("a", 1u32).to_map_key(),
+        ("a".to_string(), 1u32).to_map_key()
+    );
+    assert_eq!(
+        <Cow<str>>::from_map_key("a"),
+        Some(Cow::Owned("a".to_owned()))
+    );
+}
+
+#[derive(Debug, PartialEq, UintNewtypeImpl!, ItemStoreImpl!)]
+#[custom(uint_newtype(versioned))]
+struct VersionedFee(u16);
+
+#[derive(Debug, PartialEq, StringNewtypeImpl!, ItemStoreImpl!)]
+#[custom(string_newtype(versioned = 2))]
+struct VersionedMemo(String);
+
+#[test]
+fn versioned_values() {
+    let mut storage = MemoryStorage::default();
+
+    VersionedFee(7).save(&mut storage);
+
+    assert_eq!(storage.get(b"it::versioned_fee_u16"), Some(vec![1, 0, 7]));
+    assert_eq!(VersionedFee::load(&storage), Some(VersionedFee(7)));
+
+    storage.set(b"it::versioned_fee_u16", &[3, 0, 7]);
+
+    assert_eq!(
+        VersionedFee::try_load(&storage),
+        Err(LoadError::VersionMismatch {
+            expected: 1,
+            found: 3
+        })
+    );
+
+    VersionedMemo::new("hi").save(&mut storage);
+
+    assert_eq!(
+        storage.get(b"it::versioned_memo_string"),
+        Some(b"\x02hi".to_vec())
+    );
+    assert_eq!(
+        VersionedMemo::load(&storage),
+        Some(VersionedMemo::new("hi"))
+    );
+}
+
+#[test]
+fn map_get_or_insert_with() {
+    let mut storage = MemoryStorage::default();
+
+    let first = Balance::get_or_insert_with(&mut storage, "alice".to_owned(), || Balance(10));
+
+    assert_eq!(first, Balance(10));
+    assert_eq!(
+        Balance::load_at(&storage, "alice".to_owned()),
+        Some(Balance(10))
+    );
+
+    let second = Balance::get_or_insert_with(&mut storage, "alice".to_owned(), || {
+        panic!("already initialized")
+    });
+
+    assert_eq!(second, Balance(10));
+}
+
+#[derive(Debug, NonZeroNewtypeImpl!, ItemStoreImpl!)]
+#[custom(non_zero_newtype(eq))]
+struct Delta(NonZeroI64);
+
+#[test]
+fn signed_non_zero_storage() {
+    let mut storage = MemoryStorage::default();
+
+    let delta = Delta(NonZeroI64::new(-5).unwrap());
+
+    delta.save(&mut storage);
+
+    assert_eq!(Delta::load(&storage), Some(delta));
+
+    // sign bit flipped so negative values sort before positive ones
+    let negative = storage.get(b"it::delta_non_zero_i64").unwrap();
+
+    Delta(NonZeroI64::new(3).unwrap()).save(&mut storage);
+
+    let positive = storage.get(b"it::delta_non_zero_i64").unwrap();
+
+    assert!(negative < positive);
+}
+
+#[derive(Debug, PartialEq, UintNewtypeImpl!, ItemStoreImpl!)]
+struct Offset(i64);
+
+#[test]
+fn signed_uint_newtype_matches_non_zero_storage() {
+    let mut storage = MemoryStorage::default();
+
+    Offset(-5).save(&mut storage);
+
+    assert_eq!(Offset::load(&storage), Some(Offset(-5)));
+    assert_eq!(
+        storage.get(b"it::offset_i64"),
+        Some(<Delta as newtype_macros::ByteSerde>::to_owned_bytes(
+            &Delta(NonZeroI64::new(-5).unwrap())
+        ))
+    );
+}
+
+#[test]
+fn buffered_storage_commit_and_rollback() {
+    let mut storage = MemoryStorage::default();
+
+    Balance(1).save_at(&mut storage, "alice".to_owned());
+
+    let mut buffer = BufferedStorage::new(&mut storage);
+
+    Balance(2).save_at(&mut buffer, "alice".to_owned());
+    Balance(3).save_at(&mut buffer, "bob".to_owned());
+
+    assert_eq!(
+        Balance::load_at(&buffer, "alice".to_owned()),
+        Some(Balance(2))
+    );
+
+    buffer.rollback();
+
+    assert_eq!(
+        Balance::load_at(&storage, "alice".to_owned()),
+        Some(Balance(1))
+    );
+    assert_eq!(Balance::load_at(&storage, "bob".to_owned()), None);
+
+    let mut buffer = BufferedStorage::new(&mut storage);
+
+    Balance(3).save_at(&mut buffer, "bob".to_owned());
+    buffer.clear_key(&Balance::map_key(&"alice".to_owned()));
+
+    assert_eq!(Balance::load_at(&buffer, "alice".to_owned()), None);
+
+    buffer.commit();
+
+    assert_eq!(Balance::load_at(&storage, "alice".to_owned()), None);
+    assert_eq!(
+        Balance::load_at(&storage, "bob".to_owned()),
+        Some(Balance(3))
+    );
+}
+
+#[test]
+fn namespaced_storage_isolates_tenants() {
+    let mut storage = MemoryStorage::default();
+
+    FooUint(1).save(&mut NamespacedStorage::new(&mut storage, "tenant_a/"));
+    FooUint(2).save(&mut NamespacedStorage::new(&mut storage, "tenant_b/"));
+
+    assert_eq!(
+        FooUint::load(&ReadonlyNamespacedStorage::new(&storage, "tenant_a/")),
+        Some(FooUint(1))
+    );
+    assert_eq!(
+        FooUint::load(&NamespacedStorage::new(&mut storage, "tenant_b/")),
+        Some(FooUint(2))
+    );
+    assert!(!FooUint::exists(&storage));
+}
+
+#[test]
+fn string_and_array_lengths() {
+    let s = FooString::new("héllo");
+
+    assert_eq!(s.len(), 5);
+    assert_eq!(s.byte_len(), 6);
+    assert!(!s.is_empty());
+    assert!(FooString::new("").is_empty());
+
+    let hash = Hash([0; 32]);
+
+    assert_eq!(hash.len(), 32);
+    assert!(!hash.is_empty());
+}
+
+#[test]
+fn framed_composite_values() {
+    let mut buf = FooString::new("abc").to_owned_bytes_framed();
+    buf.extend(FooUint(9).to_owned_bytes_framed());
+
+    let (s, rest) = FooString::from_owned_bytes_framed(&buf).unwrap();
+    let (n, rest) = FooUint::from_owned_bytes_framed(rest).unwrap();
+
+    assert_eq!(s, FooString::new("abc"));
+    assert_eq!(n, FooUint(9));
+    assert!(rest.is_empty());
+
+    assert_eq!(
+        FooUint::from_owned_bytes_framed(&buf[..5]),
+        Err(LoadError::ByteLength {
+            expected: 7,
+            found: 5
+        })
+    );
+}
+
+#[derive(Debug, PartialEq, UintNewtypeImpl!, MapStoreImpl!)]
+#[custom(map_store(key, (String, String)))]
+#[custom(map_store(escape_keys))]
+struct PairCount(u32);
+
+#[test]
+fn escaped_map_keys() {
+    use newtype_macros::map::FromMapKey;
+
+    let colon = ("a:b".to_owned(), "c".to_owned());
+    let plain = ("a".to_owned(), "b:c".to_owned());
+
+    assert_eq!(colon.to_map_key(), plain.to_map_key());
+    assert_ne!(colon.to_escaped_map_key(), plain.to_escaped_map_key());
+    assert_eq!(colon.to_escaped_map_key(), r"a\:b:c");
+    assert_eq!(
+        <(String, String)>::from_escaped_map_key(&colon.to_escaped_map_key()),
+        Some(colon.clone())
+    );
+    assert_eq!(
+        <(String, String)>::from_escaped_map_key(&plain.to_escaped_map_key()),
+        Some(plain.clone())
+    );
+
+    let mut storage = MemoryStorage::default();
+
+    PairCount(1).save_at(&mut storage, &colon);
+    PairCount(2).save_at(&mut storage, &plain);
+
+    assert_eq!(PairCount::load_at(&storage, &colon), Some(PairCount(1)));
+    assert_eq!(PairCount::load_at(&storage, &plain), Some(PairCount(2)));
+    assert_eq!(
+        PairCount::iter_keys(&storage).collect::<Vec<_>>(),
+        vec![plain, colon]
+    );
+}
+
+#[test]
+fn copy_numeric_newtypes() {
+    let foo = Rank(5);
+
+    assert_eq!(foo.get(), 5);
+    assert_eq!(foo.get(), 5);
+
+    let copied = foo;
+
+    assert_eq!(copied.get() + foo.get(), 10);
+
+    let non_zero = FooNonZero::checked_new(3u8).unwrap();
+
+    assert_eq!(non_zero.get(), 3);
+    assert_eq!(non_zero.non_zero().get(), 3);
+}
+
+#[test]
+fn typed_map_storage_key() {
+    let key: newtype_macros::map::StorageKey = Balance::map_key(&"alice".to_owned());
+
+    assert_eq!(key.as_bytes(), b"it::balance_u32::alice");
+    assert_eq!(key.to_string(), "it::balance_u32::alice");
+
+    let mut storage = MemoryStorage::default();
+
+    Balance(4).save_at(&mut storage, "alice".to_owned());
+
+    assert_eq!(storage.get_key(&key), Some(vec![0, 0, 0, 4]));
+}
+
+#[test]
+fn decode_borrowed_bytes() {
+    let bytes = [0u8, 0, 0, 0, 0, 0, 0, 42, 0xff];
+
+    assert_eq!(FooUint::from_borrowed_bytes(&bytes[..8]), FooUint(42));
+    assert_eq!(
+        FooUint::try_from_borrowed_bytes(&bytes),
+        Err(LoadError::ByteLength {
+            expected: 8,
+            found: 9
+        })
+    );
+    assert_eq!(
+        SmallNonZero::try_from_borrowed_bytes(&[0]),
+        Err(LoadError::Zero)
+    );
+    assert_eq!(Hash::from_borrowed_bytes(&[7; 32]), Hash([7; 32]));
+    assert_eq!(
+        VersionedFee::try_from_borrowed_bytes(&[1, 0, 9]),
+        Ok(VersionedFee(9))
+    );
+}
+
+#[derive(Debug, PartialEq, AddressNewtypeImpl!, ItemStoreImpl!)]
+#[custom(address_newtype(hrp = "cosmos"))]
+struct Owner(String);
+
+#[derive(Debug, PartialEq, AddressNewtypeImpl!)]
+#[custom(address_newtype(hex = 4))]
+struct EvmLike(String);
+
+#[test]
+fn validated_addresses() {
+    use newtype_macros::address::AddressError;
+
+    let owner = Owner::try_new("COSMOS1QQQSYQCYQ5RQWZQFPG9SCRGWPUGPZYSNRK363E").unwrap();
+
+    assert_eq!(
+        owner.as_str(),
+        "cosmos1qqqsyqcyq5rqwzqfpg9scrgwpugpzysnrk363e"
+    );
+    assert_eq!(
+        Owner::try_new("cosmos1qqqsyqcyq5rqwzqfpg9scrgwpugpzysnrk363f"),
+        Err(AddressError::InvalidBech32)
+    );
+    assert_eq!(
+        Owner::try_new("osmo1qqqsyqcyq5rqwzqfpg9scrgwpugpzysntdz28t"),
+        Err(AddressError::WrongHrp {
+            expected: "cosmos",
+            found: "osmo".to_owned()
+        })
+    );
+
+    let mut storage = MemoryStorage::default();
+
+    owner.save(&mut storage);
+
+    assert_eq!(Owner::load(&storage), Some(owner));
+
+    storage.set(b"it::owner_string", b"cosmos1invalid");
+
+    assert_eq!(
+        Owner::try_load(&storage),
+        Err(LoadError::Address(AddressError::InvalidBech32))
+    );
+
+    assert_eq!(
+        EvmLike::try_new("0xDEADbeef").unwrap().as_str(),
+        "0xdeadbeef"
+    );
+    assert_eq!(
+        EvmLike::try_new("deadbe"),
+        Err(AddressError::InvalidHex { expected_len: 4 })
+    );
+}
+
+#[test]
+fn map_modify_at() {
+    let mut storage = MemoryStorage::default();
+
+    Balance(1).save_at(&mut storage, "alice".to_owned());
+
+    assert!(Balance::modify_at(&mut storage, "alice".to_owned(), |b| {
+        Balance(b.0 + 1)
+    }));
+    assert_eq!(
+        Balance::load_at(&storage, "alice".to_owned()),
+        Some(Balance(2))
+    );
+
+    assert!(!Balance::modify_at(&mut storage, "bob".to_owned(), |b| {
+        Balance(b.0 + 1)
+    }));
+    assert_eq!(Balance::load_at(&storage, "bob".to_owned()), None);
+    assert_eq!(storage.len(), 1);
+}
+
+mod prelude_only {
+    use macro_rules_attribute::derive;
+    use newtype_macros::prelude::*;
+    use newtype_macros::{ItemStoreImpl, UintNewtypeImpl};
+
+    #[derive(Debug, PartialEq, UintNewtypeImpl!, ItemStoreImpl!)]
+    struct Counter(u32);
+
+    #[test]
+    fn save_and_load_with_prelude_only() {
+        let mut storage = MemoryStorage::default();
+
+        Counter(3).save(&mut storage);
+
+        assert_eq!(Counter::load(&storage), Some(Counter(3)));
+        assert_eq!(
+            storage.get(b"it::prelude_only::counter_u32"),
+            Some(vec![0, 0, 0, 3])
+        );
+    }
+}
+
+#[test]
+fn map_parse_key() {
+    assert_eq!(
+        BarString::parse_key("it::bar_string_string::0:1"),
+        Some((0u32, Baz(1)))
+    );
+    assert_eq!(BarString::parse_key("it::foo_string_string::0:1"), None);
+    assert_eq!(BarString::parse_key("it::bar_string_string::x:1"), None);
+}
+
+#[derive(Debug, PartialEq)]
+struct Position {
+    x: u16,
+    y: u16,
+}
+
+impl newtype_macros::ByteSerde for Position {
+    fn from_owned_bytes(bytes: Vec<u8>) -> Self {
+        let [x0, x1, y0, y1] = bytes[..] else {
+            panic!("stored valid position")
+        };
+
+        Position {
+            x: u16::from_be_bytes([x0, x1]),
+            y: u16::from_be_bytes([y0, y1]),
+        }
+    }
+
+    fn to_owned_bytes(&self) -> Vec<u8> {
+        [self.x.to_be_bytes(), self.y.to_be_bytes()].concat()
+    }
+}
+
+impl ItemKey for Position {
+    const KEY: &'static str = "position";
+}
+
+impl newtype_macros::map::MapKeyType for Position {
+    type MapKeyType = String;
+}
+
+impl MapPrefix for Position {
+    const KEY_PREFIX: &'static str = "positions";
+}
+
+#[test]
+fn hand_written_byte_serde() {
+    let mut storage = MemoryStorage::default();
+
+    Position { x: 1, y: 2 }.save(&mut storage);
+    Position { x: 3, y: 4 }.save_at(&mut storage, "home".to_owned());
+
+    assert_eq!(Position::load(&storage), Some(Position { x: 1, y: 2 }));
+    assert_eq!(
+        Position::try_load_at(&storage, "home".to_owned()),
+        Ok(Some(Position { x: 3, y: 4 }))
+    );
+    assert_eq!(storage.keys(), vec!["position", "positions::home"]);
+}
+
+#[derive(Debug, PartialEq, UintNewtypeImpl!, MapStoreImpl!)]
+#[custom(map_store(key, (bool, u32)))]
+struct Flagged(u8);
+
+#[test]
+fn bool_map_key_component() {
+    use newtype_macros::map::FromMapKey;
+
+    assert_eq!(true.to_map_key(), "1");
+    assert_eq!(bool::from_map_key("0"), Some(false));
+    assert_eq!(bool::from_map_key("2"), None);
+
+    let mut storage = MemoryStorage::default();
+
+    Flagged(1).save_at(&mut storage, (true, 7));
+    Flagged(2).save_at(&mut storage, (false, 7));
+
+    assert_eq!(
+        storage.keys(),
+        vec!["it::flagged_u8::0:7", "it::flagged_u8::1:7"]
+    );
+    assert_eq!(
+        Flagged::iter(&storage).collect::<Vec<_>>(),
+        vec![((false, 7), Flagged(2)), ((true, 7), Flagged(1))]
+    );
+}
+
+#[derive(Debug, UintNewtypeImpl!)]
+#[custom(uint_newtype(full))]
+struct Points(u32);
+
+#[test]
+fn full_uint_extras() {
+    let points = Points::new(3u8);
+
+    assert_eq!(points.get(), 3);
+    assert!(points < Points(4));
+    assert_eq!(points.checked_add(Points(1)), Some(Points(4)));
+    assert_eq!(points.to_string(), "3");
+
+    let copied = points;
+
+    assert_eq!(copied, points);
+}
+
+#[test]
+fn uint_non_zero_bridge() {
+    assert_eq!(Small(0).try_into_non_zero::<SmallNonZero>(), None);
+    assert_eq!(
+        Small(5).try_into_non_zero::<SmallNonZero>(),
+        SmallNonZero::checked_new(5u8)
+    );
+    assert_eq!(
+        Small::from_non_zero_inner(NonZeroU8::new(5).unwrap()),
+        Small(5)
+    );
+}
+
+#[derive(Debug, Default, PartialEq, UintNewtypeImpl!, ItemStoreImpl!)]
+#[custom(item_store(default))]
+struct Nonce(u64);
+
+#[test]
+fn item_load_or_default() {
+    let mut storage = MemoryStorage::default();
+
+    assert_eq!(Nonce::load_or_default(&storage), Nonce(0));
+
+    Nonce(4).save(&mut storage);
+
+    assert_eq!(Nonce::load_or_default(&storage), Nonce(4));
+}
+
+#[test]
+fn map_save_if_absent() {
+    let mut storage = MemoryStorage::default();
+
+    assert!(!Balance::has_at(&storage, "alice".to_owned()));
+    assert!(Balance(1).save_if_absent(&mut storage, "alice".to_owned()));
+    assert!(Balance::has_at(&storage, "alice".to_owned()));
+    assert!(!Balance(2).save_if_absent(&mut storage, "alice".to_owned()));
+    assert_eq!(
+        Balance::load_at(&storage, "alice".to_owned()),
+        Some(Balance(1))
+    );
+}
+
+#[derive(PartialEq, UintNewtypeImpl!)]
+#[custom(uint_newtype(transparent_debug))]
+struct Quiet(u64);
+
+#[derive(StringNewtypeImpl!)]
+#[custom(string_newtype(transparent_debug))]
+struct QuietName(String);
+
+#[test]
+fn transparent_debug() {
+    assert_eq!(format!("{:?}", Quiet(42)), "42");
+    assert_eq!(format!("{:?}", QuietName::new("a")), "\"a\"");
+    assert_eq!(format!("{:?}", FooUint(42)), "FooUint(42)");
+}
+
+#[derive(Debug, PartialEq, VecNewtypeImpl!, ItemStoreImpl!)]
+struct Ids(Vec<FooUint>);
+
+#[test]
+fn vec_newtype_round_trip() {
+    let mut storage = MemoryStorage::default();
+
+    assert_eq!(Ids::load(&storage), None);
+
+    Ids(vec![]).save(&mut storage);
+
+    assert_eq!(Ids::load(&storage), Some(Ids(vec![])));
+
+    Ids(vec![FooUint(1), FooUint(2)]).save(&mut storage);
+
+    let ids = Ids::load(&storage).unwrap();
+
+    assert_eq!(ids.len(), 2);
+    assert_eq!(ids.into_vec(), vec![FooUint(1), FooUint(2)]);
+
+    storage.set(Ids::KEY.as_bytes(), &[0, 0, 0, 1]);
+
+    assert_eq!(
+        Ids::try_load(&storage),
+        Err(LoadError::ByteLength {
+            expected: 4,
+            found: 0
+        })
+    );
+}
+
+#[derive(Debug, PartialEq, UintNewtypeImpl!, MapKeyImpl!, MapStoreImpl!)]
+#[custom(map_store(self_key))]
+struct Member(u32);
+
+#[test]
+fn self_keyed_set() {
+    let mut storage = MemoryStorage::default();
+
+    assert!(!Member::contains(&storage, &Member(7)));
+
+    Member(7).save(&mut storage);
+
+    assert!(Member::contains(&storage, &Member(7)));
+    assert!(!Member::contains(&storage, &Member(8)));
+    assert_eq!(
+        Member::iter_keys(&storage).collect::<Vec<_>>(),
+        vec![Member(7)]
+    );
+    assert_eq!(
+        Member::iter(&storage).collect::<Vec<_>>(),
+        vec![(Member(7), Member(7))]
+    );
+    assert_eq!(Member::load_at(&storage, Member(7)), Some(Member(7)));
+
+    Member(7).remove(&mut storage);
+
+    assert!(!Member::contains(&storage, &Member(7)));
+    assert_eq!(Member::count(&storage), 0);
+}
+
+#[derive(Debug, PartialEq, UintNewtypeImpl!, ItemStoreImpl!)]
+struct Cursor(usize);
+
+#[test]
+fn usize_newtypes_store_8_bytes_on_every_platform() {
+    let mut storage = MemoryStorage::default();
+
+    Cursor(5).save(&mut storage);
+
+    assert_eq!(
+        storage.get(Cursor::KEY.as_bytes()),
+        Some(vec![0, 0, 0, 0, 0, 0, 0, 5])
+    );
+
+    storage.set(Cursor::KEY.as_bytes(), &7u64.to_be_bytes());
+
+    assert_eq!(Cursor::load(&storage), Some(Cursor(7)));
+
+    storage.set(Cursor::KEY.as_bytes(), &7u32.to_be_bytes());
+
+    assert_eq!(
+        Cursor::try_load(&storage),
+        Err(LoadError::ByteLength {
+            expected: 8,
+            found: 4
+        })
+    );
+}
+
+#[test]
+fn newtypes_have_the_size_of_their_inner_field() {
+    newtype_macros::assert_transparent_size!(FooUint, u64);
+
+    assert_eq!(std::mem::size_of::<FooUint>(), std::mem::size_of::<u64>());
+}
+
+#[test]
+#[should_panic(expected = "missing `it::foo_string_string::nobody`")]
+fn load_always_at_panics_with_the_missing_key() {
+    let storage = MemoryStorage::default();
+
+    FooString::load_always_at(&storage, "nobody".to_owned());
+}
+
+#[test]
+fn load_at_or_else() {
+    let mut storage = MemoryStorage::default();
+
+    let fallback = FooString::load_at_or_else(&storage, "a".to_owned(), || FooString::new("x"));
+
+    assert_eq!(fallback.as_str(), "x");
+    assert!(!FooString::has_at(&storage, "a".to_owned()));
+
+    FooString::new("y").save_at(&mut storage, "a".to_owned());
+
+    let loaded = FooString::load_at_or_else(&storage, "a".to_owned(), || unreachable!());
+
+    assert_eq!(loaded.as_str(), "y");
+}
+
+#[test]
+fn page_resumes_after_the_cursor() {
+    let mut storage = MemoryStorage::default();
+
+    for key in ["a", "b", "c", "d", "e"] {
+        FooString::new(key.to_uppercase()).save_at(&mut storage, key.to_owned());
+    }
+
+    let keys =
+        |page: &[(String, FooString)]| page.iter().map(|(key, _)| key.clone()).collect::<Vec<_>>();
+
+    let (page, cursor) = FooString::page(&storage, None, 2);
+
+    assert_eq!(keys(&page), ["a", "b"]);
+    assert_eq!(page[0].1.as_str(), "A");
+    assert_eq!(cursor.as_deref(), Some("b"));
+
+    let (page, cursor) = FooString::page(&storage, cursor, 2);
+
+    assert_eq!(keys(&page), ["c", "d"]);
+    assert_eq!(cursor.as_deref(), Some("d"));
+
+    let (page, cursor) = FooString::page(&storage, cursor, 2);
+
+    assert_eq!(keys(&page), ["e"]);
+    assert_eq!(cursor, None);
+}
+
+#[test]
+fn page_with_a_zero_limit_keeps_the_cursor() {
+    let mut storage = MemoryStorage::default();
+
+    for key in ["a", "b"] {
+        FooString::new(key).save_at(&mut storage, key.to_owned());
+    }
+
+    let (page, cursor) = FooString::page(&storage, Some("a".to_owned()), 0);
+
+    assert!(page.is_empty());
+    assert_eq!(cursor.as_deref(), Some("a"));
+
+    let (page, cursor) = FooString::page(&storage, cursor, 1);
+
+    assert_eq!(page, [("b".to_owned(), FooString::new("b"))]);
+    assert_eq!(cursor, None);
+}
+
+#[test]
+fn narrowing_uint_newtypes() {
+    assert_eq!(FooUint(7).try_narrow::<Baz>(), Some(Baz(7)));
+    assert_eq!(FooUint(70000).try_narrow::<Baz>(), None);
+    assert_eq!(
+        FooUint(70000).saturating_narrow::<Baz>(),
+        Some(Baz(u16::MAX))
+    );
+    assert_eq!(FooUint(7).saturating_narrow::<Baz>(), Some(Baz(7)));
+}
+
+#[test]
+fn narrowing_into_a_validated_newtype() {
+    assert_eq!(FooUint(250).try_narrow::<FeeBps>(), Some(FeeBps(250)));
+    assert_eq!(FooUint(20_000).try_narrow::<FeeBps>(), None);
+    assert_eq!(FooUint(70000).saturating_narrow::<FeeBps>(), None);
+    assert_eq!(
+        FeeBps::try_from_inner(20_000),
+        Err(ValidationError("fee of 20000 bps exceeds 100%".to_owned()))
+    );
+}
+
+#[derive(Debug, PartialEq, UintNewtypeImpl!, ItemStoreImpl!)]
+#[custom(uint_newtype(endian = "le"))]
+struct LeUint(u64);
+
+#[derive(Debug, PartialEq, NonZeroNewtypeImpl!)]
+#[custom(non_zero_newtype(endian = "le"))]
+#[custom(non_zero_newtype(checked_new))]
+struct LeDelta(NonZeroI64);
+
+#[test]
+fn little_endian_byte_layout() {
+    let mut storage = MemoryStorage::default();
+
+    LeUint(1).save(&mut storage);
+
+    assert_eq!(
+        storage.get(LeUint::KEY.as_bytes()),
+        Some(vec![1, 0, 0, 0, 0, 0, 0, 0])
+    );
+    assert_eq!(LeUint::load(&storage), Some(LeUint(1)));
+
+    FooUint(1).save(&mut storage);
+
+    assert_eq!(
+        storage.get(FooUint::KEY.as_bytes()),
+        Some(vec![0, 0, 0, 0, 0, 0, 0, 1])
+    );
+
+    let delta = LeDelta::checked_new(-2i64).unwrap();
+    let bytes = newtype_macros::ByteSerde::to_owned_bytes(&delta);
+
+    assert_eq!(bytes, (-2i64).to_le_bytes());
+    assert_eq!(
+        <LeDelta as newtype_macros::ByteSerde>::from_owned_bytes(bytes),
+        delta
+    );
+}
+
+#[test]
+fn snapshot_diff() {
+    let mut storage = MemoryStorage::default();
+
+    FooString::new("a").save_at(&mut storage, "a".to_owned());
+    FooString::new("b").save_at(&mut storage, "b".to_owned());
+
+    let before = storage.snapshot();
+
+    FooUint(1).save(&mut storage);
+
+    let diff = before.diff(&storage.snapshot());
+
+    assert_eq!(diff.added, [FooUint::KEY]);
+    assert!(diff.removed.is_empty() && diff.changed.is_empty());
+
+    let before = storage.snapshot();
+
+    FooString::new("c").save_at(&mut storage, "a".to_owned());
+    storage.clear(FooUint::KEY.as_bytes());
+
+    let diff = before.diff(&storage.snapshot());
+
+    assert_eq!(
+        diff,
+        StorageDiff {
+            added: vec![],
+            removed: vec![FooUint::KEY.to_owned()],
+            changed: vec!["it::foo_string_string::a".to_owned()],
+        }
+    );
+    assert!(storage.snapshot().diff(&storage.snapshot()).is_empty());
+}
+
+#[derive(Debug, PartialEq, UintNewtypeImpl!, MapStoreImpl!)]
+#[custom(map_store(key, (char, u32)))]
+struct Glyph(u8);
+
+#[test]
+fn char_map_key_component() {
+    use newtype_macros::map::FromMapKey;
+
+    assert_eq!('🦀'.to_map_key(), "🦀");
+    assert_eq!(char::from_map_key("🦀"), Some('🦀'));
+    assert_eq!(char::from_map_key("xy"), None);
+    assert_eq!(char::from_map_key(""), None);
+
+    let mut storage = MemoryStorage::default();
+
+    Glyph(1).save_at(&mut storage, ('x', 7));
+    Glyph(2).save_at(&mut storage, (':', 7));
+
+    assert_eq!(Glyph::load_at(&storage, ('x', 7)), Some(Glyph(1)));
+    assert_eq!(
+        Glyph::iter_keys(&storage).collect::<Vec<_>>(),
+        vec![(':', 7), ('x', 7)]
+    );
+}
+
+#[test]
+fn build_keys_at_runtime() {
+    use newtype_macros::{item, map};
+
+    assert_eq!(map::build_key("p", (1u32, 2u32)), "p::1:2");
+    assert_eq!(
+        map::build_key(FooString::KEY_PREFIX, "alice"),
+        FooString::map_key(&"alice".to_owned()).as_str()
+    );
+    assert_eq!(item::build_key(FooUint::KEY), FooUint::KEY);
+}
+
+#[derive(Debug, UintNewtypeImpl!)]
+#[custom(uint_newtype(eq_inner))]
+#[custom(uint_newtype(eq))]
+struct Level(u64);
+
+#[derive(Debug, StringNewtypeImpl!)]
+#[custom(string_newtype(eq_inner))]
+struct Label(String);
+
+#[test]
+fn compare_against_the_inner_value() {
+    assert_eq!(Level(42), 42u64);
+    assert_eq!(42u64, Level(42));
+    assert_ne!(Level(42), 41u64);
+
+    assert_eq!(Label::new("a"), "a");
+    assert_eq!("a", Label::new("a"));
+    assert!(Label::new("a") == *"a");
+}
+
+#[test]
+fn map_entry() {
+    let mut storage = MemoryStorage::default();
+
+    FooString::entry(&mut storage, "a".to_owned())
+        .or_insert(FooString::new("x"))
+        .and_modify(|s| *s = FooString::new(s.as_str().to_uppercase()));
+
+    assert_eq!(
+        FooString::load_at(&storage, "a".to_owned()),
+        Some(FooString::new("X"))
+    );
+
+    let entry = FooString::entry(&mut storage, "a".to_owned()).or_insert(FooString::new("y"));
+
+    assert_eq!(entry.get(), Some(&FooString::new("X")));
+
+    entry.save();
+
+    assert!(FooString::entry(&mut storage, "b".to_owned())
+        .and_modify(|_| unreachable!())
+        .get()
+        .is_none());
+    assert!(!FooString::has_at(&storage, "b".to_owned()));
+}
+
+#[derive(Debug, PartialEq, StringNewtypeImpl!, ItemStoreImpl!)]
+#[custom(string_newtype(compress))]
+struct Blob(String);
+
+#[test]
+fn compressed_string_round_trip() {
+    let mut storage = MemoryStorage::default();
+
+    let json = r#"{"denom":"uatom","amount":"1000"},"#.repeat(300);
+
+    assert!(json.len() > 10_000);
+
+    Blob::new(json.clone()).save(&mut storage);
+
+    assert!(storage.get(Blob::KEY.as_bytes()).unwrap().len() < json.len() / 10);
+    assert_eq!(Blob::load(&storage), Some(Blob::new(json)));
+
+    storage.set(Blob::KEY.as_bytes(), b"stored before compress");
+
+    assert_eq!(
+        Blob::load(&storage),
+        Some(Blob::new("stored before compress"))
+    );
+
+    Blob::new("tiny").save(&mut storage);
+
+    assert_eq!(storage.get(Blob::KEY.as_bytes()), Some(b"tiny".to_vec()));
+
+    storage.set(Blob::KEY.as_bytes(), &[0xff, 0x80, 0, 1]);
+
+    assert_eq!(Blob::try_load(&storage), Err(LoadError::Decompress));
+}
+
+#[test]
+fn oversized_compressed_payload_is_rejected() {
+    use newtype_macros::compress::{compress, MAX_DECOMPRESSED_LEN};
+
+    let mut storage = MemoryStorage::default();
+
+    // the `compress` header followed by a few KiB that inflate past the cap
+    let mut bomb = vec![0xff];
+    bomb.extend(miniz_oxide::deflate::compress_to_vec(
+        &vec![b'a'; MAX_DECOMPRESSED_LEN + 1],
+        6,
+    ));
+
+    assert!(bomb.len() < 64 * 1024);
+
+    storage.set(Blob::KEY.as_bytes(), &bomb);
+
+    assert_eq!(Blob::try_load(&storage), Err(LoadError::Decompress));
+
+    let long = vec![b'a'; MAX_DECOMPRESSED_LEN + 1];
+
+    assert_eq!(compress(long.clone()), long);
+}
+
+#[test]
+fn prefix_scan_on_the_first_key_component() {
+    let mut storage = MemoryStorage::default();
+
+    BarString::new("a").save_at(&mut storage, (5, Baz(1)));
+    BarString::new("b").save_at(&mut storage, (5, Baz(2)));
+    BarString::new("c").save_at(&mut storage, (6, Baz(1)));
+    BarString::new("d").save_at(&mut storage, (50, Baz(1)));
+
+    assert_eq!(
+        BarString::prefix_scan(&storage, 5u32).collect::<Vec<_>>(),
+        vec![
+            ((5, Baz(1)), BarString::new("a")),
+            ((5, Baz(2)), BarString::new("b"))
+        ]
+    );
+}
+
+#[derive(Debug, PartialEq, UintNewtypeImpl!, MapStoreImpl!)]
+#[custom(map_store(key, (String, String, u32)))]
+#[custom(map_store(escape_keys))]
+struct Hop(u64);
+
+#[test]
+fn prefix_scan_on_an_escaped_map() {
+    let mut storage = MemoryStorage::default();
+
+    let key = |a: &str, b: &str, c| (a.to_owned(), b.to_owned(), c);
+
+    Hop(1).save_at(&mut storage, key("a:b", "c", 1));
+    Hop(2).save_at(&mut storage, key("a:b", "c", 2));
+    Hop(3).save_at(&mut storage, key("a", "b:c", 1));
+    Hop(4).save_at(&mut storage, key("a:b", "cd", 1));
+
+    assert_eq!(
+        Hop::prefix_scan(&storage, ("a:b".to_owned(), "c".to_owned())).collect::<Vec<_>>(),
+        vec![(key("a:b", "c", 1), Hop(1)), (key("a:b", "c", 2), Hop(2))]
+    );
+}
+
+#[test]
+fn item_key_bytes_are_const() {
+    const KEY_BYTES: &[u8] = <FooUint as ItemKey>::KEY_BYTES;
+
+    assert_eq!(KEY_BYTES, FooUint::KEY.as_bytes());
+}
+
+#[derive(Debug, PartialEq, UintNewtypeImpl!, MapStoreImpl!)]
+#[custom(map_store(key, (std::net::IpAddr, u16)))]
+struct Hits(u64);
+
+#[test]
+fn ip_address_map_keys() {
+    use std::net::{IpAddr, Ipv4Addr, Ipv6Addr};
+
+    use newtype_macros::map::FromMapKey;
+
+    let v4 = IpAddr::V4(Ipv4Addr::new(10, 0, 0, 1));
+    let v6 = IpAddr::V6(Ipv6Addr::new(0x2001, 0xdb8, 0, 0, 0, 0, 0, 1));
+
+    assert_eq!(v4.to_map_key(), "0a000001");
+    assert_eq!(IpAddr::from_map_key("0a000001"), Some(v4));
+    assert_eq!(IpAddr::from_map_key("+a000001"), None);
+    assert!(Ipv4Addr::new(9, 0, 0, 0).to_map_key() < Ipv4Addr::new(10, 0, 0, 0).to_map_key());
+
+    let mut storage = MemoryStorage::default();
+
+    Hits(1).save_at(&mut storage, (v4, 80));
+    Hits(2).save_at(&mut storage, (v6, 443));
+
+    assert_eq!(Hits::load_at(&storage, (v6, 443)), Some(Hits(2)));
+    assert_eq!(
+        Hits::iter_keys(&storage).collect::<Vec<_>>(),
+        vec![(v4, 80), (v6, 443)]
+    );
+}
+
+#[test]
+fn map_total_bytes() {
+    let mut storage = MemoryStorage::default();
+
+    assert_eq!(FooString::total_bytes(&storage), 0);
+
+    FooString::new("xyz").save_at(&mut storage, "a".to_owned());
+    FooString::new("").save_at(&mut storage, "bc".to_owned());
+    FooUint(1).save(&mut storage);
+
+    // "it::foo_string_string::a" + "xyz", "it::foo_string_string::bc" + ""
+    assert_eq!(FooString::total_bytes(&storage), 24 + 3 + 25);
+}
+
+#[derive(Debug, PartialEq, FixedPointNewtypeImpl!, ItemStoreImpl!)]
+#[custom(fixed_point(scale = 18))]
+struct Price(u128);
+
+#[test]
+fn fixed_point_round_trip() {
+    let mut storage = MemoryStorage::default();
+
+    let price = Price::from_atomics(1_500_000_000_000_000_000);
+
+    price.save(&mut storage);
+
+    let price = Price::load(&storage).unwrap();
+
+    assert_eq!(price.to_string(), "1.5");
+    assert_eq!(price.to_f64(), 1.5);
+    assert_eq!(Price::from_atomics(2 * 10u128.pow(18)).to_string(), "2");
+    assert_eq!(Price::from_atomics(1).to_string(), "0.000000000000000001");
+}
+
+#[derive(Default)]
+struct BatchingStorage {
+    inner: MemoryStorage,
+    gets: std::cell::Cell<usize>,
+    batches: std::cell::Cell<usize>,
+}
+
+impl ReadonlyStorage for BatchingStorage {
+    fn get(&self, key: &[u8]) -> Option<Vec<u8>> {
+        self.gets.set(self.gets.get() + 1);
+        self.inner.get(key)
+    }
+
+    fn get_many(&self, keys: &[&[u8]]) -> Vec<Option<Vec<u8>>> {
+        self.batches.set(self.batches.get() + 1);
+        keys.iter().map(|key| self.inner.get(key)).collect()
+    }
+}
+
+#[test]
+fn load_many_uses_get_many() {
+    let mut storage = BatchingStorage::default();
+
+    FooString::new("x").save_at(&mut storage.inner, "a".to_owned());
+    FooString::new("z").save_at(&mut storage.inner, "c".to_owned());
+
+    let loaded = FooString::load_many(&storage, &["a".to_owned(), "b".to_owned(), "c".to_owned()]);
+
+    assert_eq!(
+        loaded,
+        vec![Some(FooString::new("x")), None, Some(FooString::new("z"))]
+    );
+    assert_eq!((storage.gets.get(), storage.batches.get()), (0, 1));
+
+    assert_eq!(
+        FooString::load_many(&&storage, &["a".to_owned()]),
+        vec![Some(FooString::new("x"))]
+    );
+    assert_eq!((storage.gets.get(), storage.batches.get()), (0, 2));
+}
+
+#[derive(Debug, PartialEq, UintNewtypeImpl!, ItemStoreImpl!)]
+#[custom(item_store(group = "config"))]
+struct MaxSupply(u128);
+
+#[derive(Debug, PartialEq, StringNewtypeImpl!, ItemStoreImpl!)]
+#[custom(item_store(group = "config"))]
+struct Admin(String);
+
+#[test]
+fn grouped_item_keys() {
+    assert_eq!(MaxSupply::KEY, "it::config::max_supply_u128");
+    assert_eq!(Admin::KEY, "it::config::admin_string");
+
+    let mut storage = MemoryStorage::default();
+
+    MaxSupply(1).save(&mut storage);
+    Admin::new("a").save(&mut storage);
+    FooUint(1).save(&mut storage);
+
+    assert_eq!(storage.prefix_iter(b"it::config::").count(), 2);
+}
+
+#[test]
+fn unwrap_into_the_inner_value() {
+    let s: String = FooString::new("x").into();
+    let n: u64 = FooUint(3).into();
+    let nz: NonZeroU128 = FooNonZero::checked_new(4u8).unwrap().into();
+
+    assert_eq!(s, "x");
+    assert_eq!(n, 3);
+    assert_eq!(nz.get(), 4);
+}
+
+#[derive(Debug, PartialEq, StringNewtypeImpl!, MapStoreImpl!)]
+#[custom(map_store(key, String))]
+#[custom(string_newtype(lossy))]
+struct LossyNote(String);
+
+#[test]
+fn invalid_utf8_is_recoverable() {
+    let mut storage = MemoryStorage::default();
+
+    let key = FooString::map_key(&"bad".to_owned());
+
+    storage.set_key(&key, &[b'o', b'k', 0xc3]);
+
+    assert_eq!(
+        FooString::try_load_at(&storage, "bad".to_owned()),
+        Err(LoadError::InvalidUtf8)
+    );
+
+    let key = LossyNote::map_key(&"bad".to_owned());
+
+    storage.set_key(&key, &[b'o', b'k', 0xc3]);
+
+    assert_eq!(
+        LossyNote::try_load_at(&storage, "bad".to_owned()),
+        Ok(Some(LossyNote::new("ok\u{fffd}")))
+    );
+}
+
+#[test]
+fn move_and_swap_at() {
+    let mut storage = MemoryStorage::default();
+
+    assert!(!FooString::move_at(
+        &mut storage,
+        "a".to_owned(),
+        "b".to_owned()
+    ));
+
+    FooString::new("x").save_at(&mut storage, "a".to_owned());
+
+    assert!(FooString::move_at(
+        &mut storage,
+        "a".to_owned(),
+        "b".to_owned()
+    ));
+    assert!(!FooString::has_at(&storage, "a".to_owned()));
+    assert_eq!(
+        FooString::load_at(&storage, "b".to_owned()),
+        Some(FooString::new("x"))
+    );
+
+    FooString::new("y").save_at(&mut storage, "c".to_owned());
+    FooString::swap_at(&mut storage, "b".to_owned(), "c".to_owned());
+
+    assert_eq!(
+        FooString::load_at(&storage, "b".to_owned()),
+        Some(FooString::new("y"))
+    );
+    assert_eq!(
+        FooString::load_at(&storage, "c".to_owned()),
+        Some(FooString::new("x"))
+    );
+
+    FooString::swap_at(&mut storage, "c".to_owned(), "d".to_owned());
+
+    assert!(!FooString::has_at(&storage, "c".to_owned()));
+    assert_eq!(
+        FooString::load_at(&storage, "d".to_owned()),
+        Some(FooString::new("x"))
+    );
+}
+
+#[test]
+fn dyn_store_persists_mixed_items() {
+    let mut storage = MemoryStorage::default();
+
+    let items: Vec<Box<dyn DynStore>> = vec![Box::new(FooUint(1)), Box::new(Admin::new("a"))];
+
+    for item in &items {
+        item.save_dyn(&mut storage);
+    }
+
+    assert_eq!(
+        items.iter().map(|item| item.key()).collect::<Vec<_>>(),
+        [FooUint::KEY, Admin::KEY]
+    );
+    assert_eq!(FooUint::load(&storage), Some(FooUint(1)));
+    assert_eq!(Admin::load(&storage), Some(Admin::new("a")));
+}
+
+#[derive(Debug, PartialEq, UintNewtypeImpl!, MapStoreImpl!)]
+#[custom(map_store(key, Option<u32>))]
+struct Bucket(u64);
+
+#[test]
+fn option_map_keys() {
+    use newtype_macros::map::FromMapKey;
+
+    assert_eq!(Some(5u32).to_map_key(), "5");
+    assert_eq!(None::<u32>.to_map_key(), "_none");
+    assert_eq!(Some("_none".to_owned()).to_map_key(), "__none");
+    assert_eq!(
+        Option::<String>::from_map_key("__none"),
+        Some(Some("_none".to_owned()))
+    );
+    assert_eq!(Option::<String>::from_map_key("_none"), Some(None));
+    assert_eq!(Option::<String>::from_map_key("_x"), None);
+
+    let mut storage = MemoryStorage::default();
+
+    Bucket(1).save_at(&mut storage, Some(5));
+    Bucket(2).save_at(&mut storage, None);
+
+    assert_eq!(Bucket::load_at(&storage, Some(5)), Some(Bucket(1)));
+    assert_eq!(Bucket::load_at(&storage, None), Some(Bucket(2)));
+    assert_eq!(
+        Bucket::iter_keys(&storage).collect::<Vec<_>>(),
+        vec![Some(5), None]
+    );
+}
+
+#[derive(Debug, PartialEq, StringNewtypeImpl!, MapStoreImpl!)]
+#[custom(map_store(key = String, clear, escape_keys))]
+struct Nickname(String);
+
+#[test]
+fn combined_map_store_attribute() {
+    let mut storage = MemoryStorage::default();
+
+    Nickname::new("al").save_at(&mut storage, "a:b".to_owned());
+
+    assert_eq!(storage.keys(), ["it::nickname_string::a\\:b"]);
+    assert_eq!(
+        Nickname::load_at(&storage, "a:b".to_owned()),
+        Some(Nickname::new("al"))
+    );
+
+    Nickname::clear_at(&mut storage, "a:b".to_owned());
+
+    assert!(storage.is_empty());
+}
+
+#[derive(Debug, PartialEq, StringNewtypeImpl!, MapStoreImpl!)]
+#[custom(map_store(key = (String, u32), prefix = "aliases", separator = "/", clear))]
+struct Alias(String);
+
+#[test]
+fn combined_map_store_attribute_with_values() {
+    let mut storage = MemoryStorage::default();
+
+    Alias::new("al").save_at(&mut storage, ("a".to_owned(), 1));
+
+    assert_eq!(storage.keys(), ["aliases::a/1"]);
+    assert_eq!(
+        Alias::load_at(&storage, ("a".to_owned(), 1)),
+        Some(Alias::new("al"))
+    );
+
+    Alias::clear_all(&mut storage);
+
+    assert!(storage.is_empty());
+}
+
+#[derive(Debug, PartialEq, MapKeyImpl!)]
+struct Coord((u32, u32));
+
+#[derive(Debug, PartialEq, UintNewtypeImpl!, MapStoreImpl!)]
+#[custom(map_store(key = (Coord, Option<(u32, u32)>), separator = "/"))]
+struct Tile(u8);
+
+#[test]
+fn custom_separator_reaches_wrapped_keys() {
+    let mut storage = MemoryStorage::default();
+
+    Tile(1).save_at(&mut storage, (Coord((1, 2)), Some((3, 4))));
+    Tile(2).save_at(&mut storage, (Coord((1, 2)), None));
+
+    assert_eq!(
+        storage.keys(),
+        ["it::tile_u8::1/2/3/4", "it::tile_u8::1/2/_none"]
+    );
+    assert_eq!(
+        Tile::iter(&storage).collect::<Vec<_>>(),
+        [
+            ((Coord((1, 2)), Some((3, 4))), Tile(1)),
+            ((Coord((1, 2)), None), Tile(2)),
+        ]
+    );
+}
+
+#[derive(Debug, PartialEq, MapFieldNewtypeImpl!, ItemStoreImpl!)]
+struct Meta(BTreeMap<String, String>);
+
+#[derive(Debug, PartialEq, MapFieldNewtypeImpl!)]
+struct Tally(HashMap<String, FooUint>);
+
+#[test]
+fn map_field_newtype_round_trip() {
+    let mut storage = MemoryStorage::default();
+
+    let meta = Meta(
+        [("b", "2"), ("a", "1")]
+            .map(|(k, v)| (k.to_owned(), v.to_owned()))
+            .into(),
+    );
+
+    meta.save(&mut storage);
+
+    assert_eq!(
+        storage.get(Meta::KEY.as_bytes()),
+        Some(vec![
+            0, 0, 0, 2, 0, 0, 0, 1, b'a', 0, 0, 0, 1, b'1', 0, 0, 0, 1, b'b', 0, 0, 0, 1, b'2'
+        ])
+    );
+    assert_eq!(Meta::load(&storage), Some(meta));
+
+    let forward = Tally(
+        [("x", 1), ("y", 2), ("z", 3)]
+            .map(|(k, v)| (k.to_owned(), FooUint(v)))
+            .into(),
+    );
+    let backward = Tally(
+        [("z", 3), ("y", 2), ("x", 1)]
+            .map(|(k, v)| (k.to_owned(), FooUint(v)))
+            .into(),
+    );
+
+    let bytes = newtype_macros::ByteSerde::to_owned_bytes(&forward);
+
+    assert_eq!(bytes, newtype_macros::ByteSerde::to_owned_bytes(&backward));
+    assert_eq!(
+        <Tally as newtype_macros::ByteSerde>::from_owned_bytes(bytes),
+        forward
+    );
+}
+
+#[test]
+fn map_store_first_and_last() {
+    let mut storage = MemoryStorage::default();
+
+    assert_eq!(AccountOwner::first(&storage), None);
+    assert_eq!(AccountOwner::last(&storage), None);
+
+    for (key, owner) in [(5, "b"), (3, "a"), (7, "c")] {
+        AccountOwner::new(owner).save_at(&mut storage, key);
+    }
+
+    assert_eq!(
+        AccountOwner::first(&storage),
+        Some((3, AccountOwner::new("a")))
+    );
+    assert_eq!(
+        AccountOwner::last(&storage),
+        Some((7, AccountOwner::new("c")))
+    );
+}
+
+#[test]
+fn map_store_order_follows_multi_digit_keys() {
+    let mut storage = MemoryStorage::default();
+
+    for key in [2u32, 9, 10] {
+        AccountOwner::new(key.to_string()).save_at(&mut storage, key);
+    }
+
+    assert_eq!(
+        AccountOwner::first(&storage),
+        Some((2, AccountOwner::new("2")))
+    );
+    assert_eq!(
+        AccountOwner::last(&storage),
+        Some((10, AccountOwner::new("10")))
+    );
+
+    let keys = |page: &[(u32, AccountOwner)]| page.iter().map(|(key, _)| *key).collect::<Vec<_>>();
+
+    let (page, cursor) = AccountOwner::page(&storage, None, 2);
+
+    assert_eq!(keys(&page), [2, 9]);
+    assert_eq!(cursor, Some(9));
+
+    let (page, cursor) = AccountOwner::page(&storage, cursor, 2);
+
+    assert_eq!(keys(&page), [10]);
+    assert_eq!(cursor, None);
+}
+
+#[test]
+fn prefix_iter_rev_is_prefix_iter_reversed() {
+    let mut storage = MemoryStorage::default();
+
+    for key in [
+        &[1][..],
+        &[1, 0xfe],
+        &[1, 0xff],
+        &[1, 0xff, 0xff, 3],
+        &[2],
+        &[0xff, 0xff],
+    ] {
+        storage.set(key, key);
+    }
+
+    for prefix in [&[][..], &[1], &[1, 0xff], &[0xff], &[3]] {
+        let mut forward = storage.prefix_iter(prefix).collect::<Vec<_>>();
+        forward.reverse();
+
+        assert_eq!(storage.prefix_iter_rev(prefix).collect::<Vec<_>>(), forward);
+    }
+}
+
+#[derive(Debug, PartialEq, UintNewtypeImpl!)]
+#[custom(uint_newtype(arithmetic))]
+struct Reserve(u128);
+
+#[derive(Debug, PartialEq, NonZeroNewtypeImpl!)]
+#[custom(non_zero_newtype(checked_new))]
+#[custom(non_zero_newtype(uint_sibling = Reserve))]
+struct Deposit(NonZeroU128);
+
+#[test]
+fn non_zero_uint_sibling() {
+    let foo = FooNonZero::checked_new(9u8).unwrap();
+
+    assert_eq!(FooNonZero::checked_new(foo.get()), Some(foo));
+
+    let deposit = Deposit::checked_new(9u8).unwrap();
+
+    assert_eq!(deposit.to_uint::<Reserve>(), Reserve(9));
+
+    let reserve = Reserve::from(deposit).checked_sub(Reserve(9)).unwrap();
+
+    assert_eq!(reserve, Reserve(0));
+    assert_eq!(Deposit::try_from(reserve), Err(Reserve(0)));
+    assert_eq!(
+        Deposit::try_from(Reserve(4)),
+        Deposit::checked_new(4u8).ok_or(Reserve(4))
+    );
+}
+
+#[test]
+fn serde_is_transparent() {
+    assert_eq!(serde_json::to_string(&FooUint(7)).unwrap(), "7");
+    assert_eq!(serde_json::from_str::<FooUint>("7").unwrap(), FooUint(7));
+
+    let non_zero = FooNonZero::checked_new(7u8).unwrap();
+
+    assert_eq!(serde_json::to_string(&non_zero).unwrap(), "7");
+    assert_eq!(serde_json::from_str::<FooNonZero>("7").unwrap(), non_zero);
+    assert!(serde_json::from_str::<FooNonZero>("0").is_err());
+
+    assert_eq!(
+        serde_json::to_string(&FooString::new("x")).unwrap(),
+        r#""x""#
+    );
+    assert_eq!(
+        serde_json::from_str::<FooString>(r#""x""#)
+            .unwrap()
+            .as_str(),
+        "x"
+    );
+    assert!(serde_json::from_str::<Ticker>(r#""x""#).is_err());
+}
+
+#[test]
+fn bincode_sees_the_bare_inner_value() {
+    let encoded = bincode::serialize(&FooUint(7)).unwrap();
+
+    assert_eq!(encoded, bincode::serialize(&7u64).unwrap());
+    assert_eq!(
+        bincode::deserialize::<FooUint>(&encoded).unwrap(),
+        FooUint(7)
+    );
+
+    let non_zero = FooNonZero::checked_new(7u8).unwrap();
+    let encoded = bincode::serialize(&non_zero).unwrap();
+
+    assert_eq!(encoded, bincode::serialize(&7u128).unwrap());
+    assert_eq!(
+        bincode::deserialize::<FooNonZero>(&encoded).unwrap(),
+        non_zero
+    );
+    assert!(bincode::deserialize::<FooNonZero>(&bincode::serialize(&0u128).unwrap()).is_err());
+
+    let encoded = bincode::serialize(&FooString::new("x")).unwrap();
+
+    assert_eq!(encoded, bincode::serialize("x").unwrap());
+    assert_eq!(
+        bincode::deserialize::<FooString>(&encoded)
+            .unwrap()
+            .as_str(),
+        "x"
+    );
+}
+
+#[derive(Debug, Default, PartialEq, UintNewtypeImpl!, ItemStoreImpl!)]
+#[custom(item_store(clear))]
+#[custom(item_store(default))]
+struct Epoch(u64);
+
+#[test]
+fn item_clear_and_load_or_default() {
+    let mut storage = MemoryStorage::default();
+
+    Epoch(3).save(&mut storage);
+
+    assert_eq!(Epoch::load_or_default(&storage), Epoch(3));
+
+    Epoch::clear(&mut storage);
+
+    assert!(!Epoch::exists(&storage));
+    assert_eq!(Epoch::load_or_default(&storage), Epoch(0));
+}
+
+#[test]
+fn map_store_retain() {
+    let mut storage = MemoryStorage::default();
+
+    for key in 1..=4 {
+        Denom::new("x").save_at(&mut storage, key);
+    }
+
+    Denom::retain(&mut storage, |key, _| key % 2 == 0);
+
+    assert_eq!(Denom::iter_keys(&storage).collect::<Vec<_>>(), [2, 4]);
+}
+
+#[derive(Debug, PartialEq, U256NewtypeImpl!, ItemStoreImpl!)]
+struct TotalSupply(U256);
+
+#[test]
+fn u256_newtype_round_trip() {
+    let mut storage = MemoryStorage::default();
+
+    let mut bytes = [0xab; 32];
+    bytes[0] = 0x7f;
+
+    let supply = TotalSupply(U256::from_big_endian(&bytes));
+
+    supply.save(&mut storage);
+
+    assert_eq!(
+        storage.get(TotalSupply::KEY.as_bytes()),
+        Some(bytes.to_vec())
+    );
+    assert_eq!(
+        TotalSupply::load(&storage),
+        Some(TotalSupply(U256::from_big_endian(&bytes)))
+    );
+
+    storage.set(TotalSupply::KEY.as_bytes(), &[0; 16]);
+
+    assert_eq!(
+        TotalSupply::try_load(&storage),
+        Err(LoadError::ByteLength {
+            expected: 32,
+            found: 16
+        })
+    );
+
+    let one = TotalSupply(U256::from(1u8));
+
+    assert_eq!(
+        TotalSupply(U256::from(u128::MAX))
+            .checked_add(one)
+            .unwrap()
+            .get(),
+        U256::from_big_endian(&{
+            let mut carried = [0; 32];
+            carried[15] = 1;
+            carried
+        })
+    );
+    assert_eq!(
+        TotalSupply(U256::MAX).checked_add(TotalSupply(U256::from(1u8))),
+        None
+    );
+    assert_eq!(
+        TotalSupply(U256::zero()).checked_sub(TotalSupply(U256::from(1u8))),
+        None
+    );
+    assert_eq!(u128::try_from(U256::from(7u8)), Ok(7));
+}
+
+#[test]
+fn array_newtype_bytes_and_hex() {
+    fn digest(bytes: impl AsRef<[u8]>) -> u32 {
+        bytes.as_ref().iter().map(|b| u32::from(*b)).sum()
+    }
+
+    let mut bytes = [0u8; 32];
+    bytes[0] = 0xab;
+    bytes[31] = 0x01;
+
+    let hash = Hash(bytes);
+
+    assert_eq!(digest(&hash), 0xac);
+
+    let hex = hash.to_hex();
+
+    assert_eq!(
+        hex,
+        "ab00000000000000000000000000000000000000000000000000000000000001"
+    );
+    assert_eq!(Hash::from_hex(&hex.to_uppercase()), Ok(Hash(bytes)));
+    assert_eq!(
+        Hash::from_hex("ab01"),
+        Err(newtype_macros::array::HexError { expected_len: 32 })
+    );
+}
+
+/// fails every write after the first
+#[derive(Default)]
+struct FlakyStorage {
+    inner: MemoryStorage,
+    writes: usize,
+}
+
+impl ReadonlyStorage for FlakyStorage {
+    fn get(&self, key: &[u8]) -> Option<Vec<u8>> {
+        self.inner.get(key)
+    }
+}
+
+impl TryMutableStorage for FlakyStorage {
+    fn try_set(&mut self, key: &[u8], value: &[u8]) -> Result<(), StorageError> {
+        self.writes += 1;
+
+        if self.writes > 1 {
+            return Err(StorageError("connection lost".to_owned()));
+        }
+
+        self.inner.set(key, value);
+
+        Ok(())
+    }
+
+    fn try_clear(&mut self, key: &[u8]) -> Result<(), StorageError> {
+        self.inner.clear(key);
+
+        Ok(())
+    }
+}
+
+#[test]
+fn fallible_storage_writes() {
+    let mut storage = FlakyStorage::default();
+
+    assert_eq!(FooUint(1).try_save(&mut storage), Ok(()));
+    assert_eq!(
+        FooString::new("x").try_save_at(&mut storage, "a".to_owned()),
+        Err(StorageError("connection lost".to_owned()))
+    );
+    assert_eq!(FooUint::load(&storage), Some(FooUint(1)));
+    assert!(!FooString::has_at(&storage, "a".to_owned()));
+
+    let mut storage = MemoryStorage::default();
+
+    assert_eq!(FooUint(2).try_save(&mut storage), Ok(()));
+    assert_eq!(FooUint::load(&storage), Some(FooUint(2)));
+}
+
+#[test]
+fn map_store_to_btree_map() {
+    let mut storage = MemoryStorage::default();
+
+    for (key, value) in [("b", "B"), ("c", "C"), ("a", "A")] {
+        FooString::new(value).save_at(&mut storage, key.to_owned());
+    }
+
+    let map = FooString::to_btree_map(&storage);
+
+    assert_eq!(
+        map.iter()
+            .map(|(key, value)| (key.as_str(), value.as_str()))
+            .collect::<Vec<_>>(),
+        [("a", "A"), ("b", "B"), ("c", "C")]
+    );
+}
+
+#[test]
+fn flat_and_nested_tuple_keys_match() {
+    assert_eq!(
+        (7u32, ("x".to_owned(), true)).into_map_key(),
+        (7u32, "x".to_owned(), true).into_map_key()
+    );
+    assert_eq!(
+        (1u8, (2u16, (3u32, 4u64))).into_map_key(),
+        (1u8, 2u16, 3u32, 4u64).into_map_key()
+    );
+    assert_eq!(
+        (Baz(1), "a:b".to_owned(), 2u32).to_escaped_map_key(),
+        (Baz(1), ("a:b".to_owned(), 2u32)).to_escaped_map_key()
+    );
+    assert_eq!(
+        <(u32, String, bool) as FromMapKey>::from_map_key("7:x:1"),
+        Some((7, "x".to_owned(), true))
+    );
+}
+
+#[derive(Debug, PartialEq, UintNewtypeImpl!, MapStoreImpl!)]
+#[custom(map_store(key = String, counter))]
+struct Votes(u32);
+
+#[test]
+fn map_store_counter() {
+    let mut storage = MemoryStorage::default();
+
+    assert_eq!(Votes::increment_at(&mut storage, "yes".to_owned()), Ok(1));
+    assert_eq!(Votes::increment_at(&mut storage, "yes".to_owned()), Ok(2));
+    assert_eq!(Votes::increment_at(&mut storage, "no".to_owned()), Ok(1));
+    assert_eq!(
+        Votes::decrement_at(&mut storage, "yes".to_owned()),
+        Ok(Some(1))
+    );
+
+    assert_eq!(Votes::load_at(&storage, "yes".to_owned()), Some(Votes(1)));
+    assert_eq!(Votes::load_at(&storage, "no".to_owned()), Some(Votes(1)));
+}
+
+#[test]
+fn map_store_counter_decrement_stops_at_zero() {
+    let mut storage = MemoryStorage::default();
+
+    assert_eq!(
+        Votes::decrement_at(&mut storage, "yes".to_owned()),
+        Ok(None)
+    );
+    assert!(storage.is_empty());
+
+    Votes::increment_at(&mut storage, "yes".to_owned()).unwrap();
+
+    assert_eq!(
+        Votes::decrement_at(&mut storage, "yes".to_owned()),
+        Ok(Some(0))
+    );
+    assert_eq!(
+        Votes::decrement_at(&mut storage, "yes".to_owned()),
+        Ok(None)
+    );
+    assert_eq!(Votes::load_at(&storage, "yes".to_owned()), Some(Votes(0)));
+}
+
+#[derive(Debug, PartialEq, UintNewtypeImpl!, ItemStoreImpl!)]
+#[custom(uint_newtype(validate))]
+#[custom(uint_newtype(arithmetic))]
+struct FeeBps(u16);
+
+impl Validate for FeeBps {
+    fn validate(&self) -> Result<(), ValidationError> {
+        if self.0 > 10_000 {
+            return Err(ValidationError(format!(
+                "fee of {} bps exceeds 100%",
+                self.0
+            )));
+        }
+
+        Ok(())
+    }
+}
+
+#[test]
+fn validated_uint_newtype() {
+    let mut storage = MemoryStorage::default();
+
+    assert_eq!(FeeBps::try_new(30u8), Ok(FeeBps(30)));
+    assert_eq!(
+        FeeBps::try_new(10_001u16),
+        Err(ValidationError("fee of 10001 bps exceeds 100%".to_owned()))
+    );
+
+    FeeBps(30).save(&mut storage);
+
+    assert_eq!(FeeBps::try_load(&storage), Ok(Some(FeeBps(30))));
+
+    storage.set(FeeBps::KEY.as_bytes(), &10_001u16.to_be_bytes());
+
+    assert_eq!(
+        FeeBps::try_load(&storage),
+        Err(LoadError::Validation(ValidationError(
+            "fee of 10001 bps exceeds 100%".to_owned()
+        )))
+    );
+}
+
+#[test]
+fn validated_uint_newtype_deserialize() {
+    assert_eq!(serde_json::from_str::<FeeBps>("30").unwrap(), FeeBps(30));
+    assert_eq!(
+        serde_json::from_str::<FeeBps>("10001")
+            .unwrap_err()
+            .to_string(),
+        "invalid value: fee of 10001 bps exceeds 100%"
+    );
+}
+
+#[test]
+#[should_panic(expected = "fee of 10001 bps exceeds 100%")]
+fn validated_uint_newtype_from_inner_panics() {
+    let _ = FeeBps::from_inner(10_001);
+}
+
+#[derive(Debug, PartialEq, NonZeroNewtypeImpl!, ItemStoreImpl!)]
+#[custom(non_zero_newtype(validate))]
+#[custom(non_zero_newtype(checked_new))]
+#[custom(non_zero_newtype(arithmetic))]
+struct Multiplier(NonZeroU16);
+
+impl Validate for Multiplier {
+    fn validate(&self) -> Result<(), ValidationError> {
+        if self.0.get() > 100 {
+            return Err(ValidationError(format!(
+                "multiplier of {} exceeds 100x",
+                self.0
+            )));
+        }
+
+        Ok(())
+    }
+}
+
+#[test]
+fn validated_non_zero_newtype() {
+    let ten = NonZeroU16::new(10).unwrap();
+    let too_many = NonZeroU16::new(101).unwrap();
+
+    assert_eq!(Multiplier::try_new(ten), Ok(Multiplier(ten)));
+    assert_eq!(
+        Multiplier::try_new(too_many),
+        Err(ValidationError("multiplier of 101 exceeds 100x".to_owned()))
+    );
+    assert_eq!(Multiplier::checked_new(10u16), Some(Multiplier(ten)));
+    assert_eq!(Multiplier::checked_new(101u16), None);
+    assert_eq!(
+        Multiplier(ten).map_inner(|inner| inner * 2),
+        Some(Multiplier::from_inner(NonZeroU16::new(20).unwrap()))
+    );
+    assert!(serde_json::from_str::<Multiplier>("101").is_err());
+
+    let mut storage = MemoryStorage::default();
+
+    storage.set(Multiplier::KEY.as_bytes(), &101u16.to_be_bytes());
+
+    assert_eq!(
+        Multiplier::try_load(&storage),
+        Err(LoadError::Validation(ValidationError(
+            "multiplier of 101 exceeds 100x".to_owned()
+        )))
+    );
+}
+
+#[test]
+#[should_panic(expected = "multiplier of 101 exceeds 100x")]
+fn validated_non_zero_newtype_map_inner_panics() {
+    let _ = Multiplier(NonZeroU16::new(10).unwrap()).map_inner(|_| 101);
+}
+
+#[test]
+fn validated_arithmetic() {
+    assert_eq!(
+        FeeBps(9_000).checked_add(FeeBps(1_000)),
+        Some(FeeBps(10_000))
+    );
+    assert_eq!(FeeBps(9_000).checked_add(FeeBps(1_001)), None);
+    assert_eq!(FeeBps(10_001).checked_sub(FeeBps(1)), Some(FeeBps(10_000)));
+    assert_eq!(FeeBps(10_003).checked_sub(FeeBps(1)), None);
+
+    let m = |n| Multiplier(NonZeroU16::new(n).unwrap());
+
+    assert_eq!(m(60).checked_add(m(40)), Some(m(100)));
+    assert_eq!(m(60).checked_add(m(41)), None);
+    assert_eq!(m(102).checked_sub(m(1)), None);
+}
+
+#[test]
+#[should_panic(expected = "fee of 65535 bps exceeds 100%")]
+fn validated_saturating_add_panics() {
+    let _ = FeeBps(9_000).saturating_add(FeeBps(u16::MAX));
+}
+
+#[test]
+#[should_panic(expected = "fee of 10001 bps exceeds 100%")]
+fn validated_saturating_sub_panics() {
+    let _ = FeeBps(10_002).saturating_sub(FeeBps(1));
+}
+
+#[test]
+#[should_panic(expected = "multiplier of 65535 exceeds 100x")]
+fn validated_non_zero_saturating_add_panics() {
+    let m = |n| Multiplier(NonZeroU16::new(n).unwrap());
+
+    let _ = m(1).saturating_add(m(u16::MAX));
+}
+
+#[derive(Debug, PartialEq, UintNewtypeImpl!, MapStoreImpl!)]
+#[custom(uint_newtype(validate))]
+#[custom(map_store(key = String, counter))]
+struct Seats(u8);
+
+impl Validate for Seats {
+    fn validate(&self) -> Result<(), ValidationError> {
+        if !(1..=2).contains(&self.0) {
+            return Err(ValidationError(format!("{} seats, 1 to 2", self.0)));
+        }
+
+        Ok(())
+    }
+}
+
+#[test]
+fn validated_counter() {
+    let mut storage = MemoryStorage::default();
+
+    assert_eq!(Seats::increment_at(&mut storage, "a".to_owned()), Ok(1));
+    assert_eq!(Seats::increment_at(&mut storage, "a".to_owned()), Ok(2));
+    assert_eq!(
+        Seats::increment_at(&mut storage, "a".to_owned()),
+        Err(ValidationError("3 seats, 1 to 2".to_owned()))
+    );
+    assert_eq!(Seats::load_at(&storage, "a".to_owned()), Some(Seats(2)));
+
+    assert_eq!(Seats::increment_at(&mut storage, "b".to_owned()), Ok(1));
+
+    assert_eq!(
+        Seats::decrement_at(&mut storage, "b".to_owned()),
+        Err(ValidationError("0 seats, 1 to 2".to_owned()))
+    );
+    assert_eq!(Seats::load_at(&storage, "b".to_owned()), Some(Seats(1)));
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, TimestampNewtypeImpl!)]
+#[custom(uint_newtype(validate))]
+struct Lease(u64);
+
+impl Validate for Lease {
+    fn validate(&self) -> Result<(), ValidationError> {
+        if self.0 > 60_000_000_000 {
+            return Err(ValidationError(format!(
+                "lease of {}ns over a minute",
+                self.0
+            )));
+        }
+
+        Ok(())
+    }
+}
+
+#[test]
+fn validated_timestamp() {
+    let half = Lease::from_duration(Duration::from_secs(30)).unwrap();
+
+    assert_eq!(Lease::from_duration(Duration::from_secs(61)), None);
+    assert_eq!(half.plus(half), Some(Lease::from_nanos(60_000_000_000)));
+    assert_eq!(half.plus(Lease::from_nanos(30_000_000_001)), None);
+}
+
+#[test]
+#[should_panic(expected = "lease of 60000000001ns over a minute")]
+fn validated_timestamp_from_nanos_panics() {
+    let _ = Lease::from_nanos(60_000_000_001);
+}
+
+#[derive(Debug, PartialEq, FixedPointNewtypeImpl!)]
+#[custom(fixed_point(scale = 2))]
+#[custom(uint_newtype(validate))]
+struct Ratio(u128);
+
+impl Validate for Ratio {
+    fn validate(&self) -> Result<(), ValidationError> {
+        if self.0 > 100 {
+            return Err(ValidationError(format!("ratio {self} above 1")));
+        }
+
+        Ok(())
+    }
+}
+
+#[test]
+#[should_panic(expected = "ratio 1.01 above 1")]
+fn validated_fixed_point_from_atomics_panics() {
+    assert_eq!(Ratio::from_atomics(100).to_string(), "1");
+
+    let _ = Ratio::from_atomics(101);
+}
+
+#[derive(Debug, PartialEq, StringNewtypeImpl!, ItemStoreImpl!)]
+#[custom(string_newtype(validate))]
+#[custom(string_newtype(len(max = 8)))]
+struct Slug(String);
+
+impl Validate for Slug {
+    fn validate(&self) -> Result<(), ValidationError> {
+        if self.0.chars().any(|c| !c.is_ascii_lowercase()) {
+            return Err(ValidationError(format!(
+                "`{}` isn't a lowercase slug",
+                self.0
+            )));
+        }
+
+        Ok(())
+    }
+}
+
+#[test]
+fn validated_string_newtype() {
+    assert_eq!(Slug::try_new("atom"), Ok(Slug("atom".to_owned())));
+    assert_eq!(
+        Slug::try_new("Atom"),
+        Err(StringError::Validation(ValidationError(
+            "`Atom` isn't a lowercase slug".to_owned()
+        )))
+    );
+    assert!(matches!(
+        Slug::try_new("abcdefghi"),
+        Err(StringError::Len(_))
+    ));
+
+    assert_eq!(
+        serde_json::from_str::<Slug>("\"Atom\"")
+            .unwrap_err()
+            .to_string(),
+        "invalid value: `Atom` isn't a lowercase slug"
+    );
+
+    let mut storage = MemoryStorage::default();
+
+    storage.set(Slug::KEY.as_bytes(), b"Atom");
+
+    assert_eq!(
+        Slug::try_load(&storage),
+        Err(LoadError::Validation(ValidationError(
+            "`Atom` isn't a lowercase slug".to_owned()
+        )))
+    );
+}
+
+#[test]
+#[should_panic(expected = "`Atom` isn't a lowercase slug")]
+fn validated_string_newtype_new_panics() {
+    let _ = Slug::new("Atom");
+}
+
+#[derive(Debug, PartialEq, UintNewtypeImpl!, ItemStoreImpl!)]
+#[custom(item_store(keep_previous))]
+struct Threshold(u32);
+
+#[test]
+fn item_keep_previous() {
+    let mut storage = MemoryStorage::default();
+
+    Threshold(1).save_keeping_previous(&mut storage);
+
+    assert_eq!(Threshold::load_previous(&storage), None);
+
+    Threshold(2).save_keeping_previous(&mut storage);
+
+    assert_eq!(Threshold::load(&storage), Some(Threshold(2)));
+    assert_eq!(Threshold::load_previous(&storage), Some(Threshold(1)));
+    assert!(storage.get(b"it::threshold_u32::prev").is_some());
+}
+
+/// a backend that can only be written to
+#[derive(Default)]
+struct WriteOnly(Vec<(Vec<u8>, Option<Vec<u8>>)>);
+
+impl MutableStorage for WriteOnly {
+    fn set(&mut self, key: &[u8], value: &[u8]) {
+        self.0.push((key.to_owned(), Some(value.to_owned())));
+    }
+
+    fn clear(&mut self, key: &[u8]) {
+        self.0.push((key.to_owned(), None));
+    }
+}
+
+#[test]
+fn write_only_storage_saves_and_clears() {
+    let mut storage = WriteOnly::default();
+
+    FooUint(1).save(&mut storage);
+    FooString::new("x").save_at(&mut storage, "a".to_owned());
+    BarString::clear_at(&mut storage, (1, Baz::new(2u8)));
+
+    assert_eq!(storage.0.len(), 3);
+    assert_eq!(
+        storage.0[0],
+        (
+            b"it::foo_uint_u64".to_vec(),
+            Some(vec![0, 0, 0, 0, 0, 0, 0, 1])
+        )
+    );
+    assert_eq!(storage.0[2].1, None);
+}
+
+#[test]
+fn byte_map_keys_are_hex() {
+    let bytes = vec![0x3a, 0x00];
+
+    assert_eq!(bytes.to_map_key(), "3a00");
+    assert_eq!(bytes.as_slice().into_map_key(), "3a00");
+    assert_eq!(
+        <Vec<u8> as FromMapKey>::from_map_key("3a00"),
+        Some(bytes.clone())
+    );
+    assert_eq!(<Vec<u8> as FromMapKey>::from_map_key("3A00"), None);
+
+    let binary = (bytes, 1u32).into_map_key();
+    let string = (":\0".to_owned(), 1u32).into_map_key();
+
+    assert_no_collisions(&[&binary, &string]);
+    assert_eq!(binary.matches(':').count(), 1);
+}
+
+#[test]
+fn double_map_iter_prefix() {
+    let mut storage = MemoryStorage::default();
+
+    BarString::new("x").save_at(&mut storage, (1, Baz(1)));
+    BarString::new("y").save_at(&mut storage, (1, Baz(2)));
+    BarString::new("z").save_at(&mut storage, (2, Baz(1)));
+    BarString::new("w").save_at(&mut storage, (11, Baz(1)));
+
+    let entries: Vec<_> = BarString::iter_prefix(&storage, 1)
+        .map(|(secondary, value)| (secondary, value.as_str().to_owned()))
+        .collect();
+
+    assert_eq!(
+        entries,
+        [(Baz(1), "x".to_owned()), (Baz(2), "y".to_owned())]
+    );
+}
+
+#[test]
+fn try_from_bytes() {
+    assert_eq!(
+        FooUint::try_from(vec![0, 0, 0, 0, 0, 0, 0, 7]),
+        Ok(FooUint(7))
+    );
+    assert_eq!(
+        FooUint::try_from([0u8, 0, 0, 0, 0, 0, 0, 7].as_slice()),
+        Ok(FooUint(7))
+    );
+    assert_eq!(
+        FooUint::try_from(vec![7]),
+        Err(LoadError::ByteLength {
+            expected: 8,
+            found: 1
+        })
+    );
+    assert_eq!(
+        FooNonZero::try_from(vec![0; 16]).map(|foo| foo.get()),
+        Err(LoadError::Zero)
+    );
+}
+
+#[test]
+fn fixed_byte_lengths() {
+    use newtype_macros::ByteSerde;
+
+    assert_eq!(FooUint::BYTE_LEN, 8);
+    assert_eq!(FooUint::FIXED_LEN, Some(8));
+    assert_eq!(FooNonZero::FIXED_LEN, Some(16));
+    assert_eq!(Hash::FIXED_LEN, Some(32));
+    assert_eq!(TotalSupply::FIXED_LEN, Some(32));
+    assert_eq!(Cursor::FIXED_LEN, Some(8));
+    assert_eq!(VersionedFee::BYTE_LEN, 2);
+    assert_eq!(VersionedFee::FIXED_LEN, Some(3));
+    assert_eq!(FooString::FIXED_LEN, None);
+}
+
+#[derive(Debug, PartialEq, StringNewtypeImpl!, MapStoreImpl!)]
+#[custom(map_store(key = String, lowercase_keys))]
+struct DisplayName(String);
+
+#[test]
+fn case_insensitive_map_keys() {
+    let mut storage = MemoryStorage::default();
+
+    DisplayName::new("Alice Smith").save_at(&mut storage, "Alice".to_owned());
+
+    assert_eq!(
+        DisplayName::load_at(&storage, "alice".to_owned()),
+        Some(DisplayName::new("Alice Smith"))
+    );
+    assert!(DisplayName::has_at(&storage, "ALICE".to_owned()));
+    assert_eq!(
+        DisplayName::iter_keys(&storage).collect::<Vec<_>>(),
+        ["alice"]
+    );
+}
+
+mod split_prelude {
+    use macro_rules_attribute::derive;
+    use newtype_macros::prelude::uint::*;
+    use newtype_macros::UintNewtypeImpl;
+
+    #[derive(Debug, PartialEq, UintNewtypeImpl!)]
+    #[custom(uint_newtype(new))]
+    #[custom(uint_newtype(arithmetic))]
+    struct Shares(u64);
+
+    #[test]
+    fn uint_prelude_only() {
+        let shares = Shares::new(4u8);
+
+        assert_eq!(shares.get(), 4);
+        assert_eq!(shares.checked_add(Shares(1)), Some(Shares(5)));
+    }
+}
+
+#[test]
+fn map_store_exists_any() {
+    let mut storage = MemoryStorage::default();
+
+    FooUint(1).save(&mut storage);
+
+    assert!(!FooString::exists_any(&storage));
+
+    FooString::new("a").save_at(&mut storage, "a".to_owned());
+
+    assert!(FooString::exists_any(&storage));
+    assert!(!BarString::exists_any(&storage));
+}
+
+#[derive(Debug, PartialEq, UintNewtypeImpl!, MapStoreImpl!)]
+#[custom(map_store(key, (u32, u32)))]
+#[custom(map_store(separator = "/"))]
+struct Route(u64);
+
+#[test]
+fn custom_map_key_separator() {
+    let mut storage = MemoryStorage::default();
+
+    Route(7).save_at(&mut storage, (1, 2));
+    Route(8).save_at(&mut storage, (1, 3));
+    Route(9).save_at(&mut storage, (11, 2));
+
+    assert_eq!(Route::map_key(&(1, 2)).as_str(), "it::route_u64::1/2");
+    assert_eq!(Route::load_at(&storage, (1, 2)), Some(Route(7)));
+    assert_eq!(
+        Route::iter_keys(&storage).collect::<Vec<_>>(),
+        [(1, 2), (1, 3), (11, 2)]
+    );
+    assert_eq!(
+        Route::iter_prefix(&storage, 1).collect::<Vec<_>>(),
+        [(2, Route(7)), (3, Route(8))]
+    );
+}
+
+#[test]
+fn map_inner_combinators() {
+    assert_eq!(FooUint(21).map_inner(|x| x * 2), FooUint(42));
+
+    let non_zero = FooNonZero::checked_new(3u8).unwrap();
+
+    assert_eq!(non_zero.map_inner(|x| x * 2), FooNonZero::checked_new(6u8));
+    assert_eq!(
+        FooNonZero::checked_new(3u8).unwrap().map_inner(|x| x - 3),
+        None
+    );
+}
+
+#[test]
+fn from_inner_constructors() {
+    fn double<T: UintNewtype<PrimitiveInner = u64>>(t: T) -> T {
+        T::from_inner(t.get() * 2)
+    }
+
+    assert_eq!(<FooUint as UintNewtype>::from_inner(7), FooUint(7));
+    assert_eq!(double(FooUint(7)), FooUint(14));
+    assert_eq!(
+        <FooNonZero as NonZeroNewtype>::from_inner(NonZeroU128::new(7).unwrap()),
+        FooNonZero::checked_new(7u8).unwrap()
+    );
+    assert_eq!(
+        <FooString as StringNewtype>::from_inner("x".to_owned()).as_str(),
+        "x"
+    );
+    assert_eq!(
+        <Denom as StringNewtype>::from_inner("x".to_owned()),
+        Denom::new("x")
+    );
+    assert_eq!(<Hash as ArrayNewtype>::from_inner([1; 32]), Hash([1; 32]));
+}
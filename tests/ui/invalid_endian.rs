@@ -0,0 +1,8 @@
+use macro_rules_attribute::derive;
+use newtype_macros::UintNewtypeImpl;
+
+#[derive(UintNewtypeImpl!)]
+#[custom(uint_newtype(endian = "little"))]
+struct Amount(u64);
+
+fn main() {}
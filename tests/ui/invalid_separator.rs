@@ -0,0 +1,9 @@
+use macro_rules_attribute::derive;
+use newtype_macros::{MapStoreImpl, UintNewtypeImpl};
+
+#[derive(UintNewtypeImpl!, MapStoreImpl!)]
+#[custom(map_store(key, (u32, u32)))]
+#[custom(map_store(separator = "ab"))]
+struct Amount(u64);
+
+fn main() {}
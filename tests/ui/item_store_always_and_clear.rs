@@ -0,0 +1,9 @@
+use macro_rules_attribute::derive;
+use newtype_macros::{ItemStoreImpl, UintNewtypeImpl};
+
+#[derive(UintNewtypeImpl!, ItemStoreImpl!)]
+#[custom(item_store(always))]
+#[custom(item_store(clear))]
+struct Amount(u64);
+
+fn main() {}
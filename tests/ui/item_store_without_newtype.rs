@@ -0,0 +1,7 @@
+use macro_rules_attribute::derive;
+use newtype_macros::ItemStoreImpl;
+
+#[derive(ItemStoreImpl!)]
+struct Amount(u64);
+
+fn main() {}
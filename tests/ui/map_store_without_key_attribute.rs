@@ -0,0 +1,8 @@
+use macro_rules_attribute::derive;
+use newtype_macros::{MapStoreImpl, StringNewtypeImpl};
+
+#[derive(StringNewtypeImpl!, MapStoreImpl!)]
+#[custom(map_store(clear))]
+struct Name(String);
+
+fn main() {}
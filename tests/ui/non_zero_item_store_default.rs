@@ -0,0 +1,10 @@
+use std::num::NonZeroU64;
+
+use macro_rules_attribute::derive;
+use newtype_macros::{ItemStoreImpl, NonZeroNewtypeImpl};
+
+#[derive(NonZeroNewtypeImpl!, ItemStoreImpl!)]
+#[custom(item_store(default))]
+struct Amount(NonZeroU64);
+
+fn main() {}
@@ -0,0 +1,10 @@
+use std::num::NonZeroU64;
+
+use macro_rules_attribute::derive;
+use newtype_macros::NonZeroNewtypeImpl;
+
+#[derive(NonZeroNewtypeImpl!)]
+#[custom(non_zero_newtype(default))]
+struct Amount(NonZeroU64);
+
+fn main() {}
@@ -0,0 +1,12 @@
+#![deny(warnings)]
+
+use macro_rules_attribute::derive;
+use newtype_macros::{uint::New, UintNewtypeImpl};
+
+#[derive(UintNewtypeImpl!)]
+#[custom(uint_newtype(new))]
+struct Amount(u64);
+
+fn main() {
+    Amount::new(1u64);
+}